@@ -1,7 +1,13 @@
 use anchor_lang::prelude::*;
 
+/// Seed of the config PDA of a program-controlled collection.
 #[constant]
-pub const SEED: &str = "anchor";
+pub const COLLECTION_CONFIG_SEED: &str = "collection_config";
+
+/// Seed of the PDA that is the update authority of a program-controlled collection,
+/// derived from the collection config.
+#[constant]
+pub const COLLECTION_AUTHORITY_SEED: &str = "collection_authority";
 
 #[constant]
 pub const MPL_CORE_ID: Pubkey =
@@ -14,3 +20,87 @@ pub const ONCHAIN_METAPLEX_ORACLE_PLUGIN: Pubkey =
 #[constant]
 pub const SPL_NOOP_PROGRAM: Pubkey =
     Pubkey::from_str_const("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+/// Longest name accepted for assets and collections, and for the name template of a mint config.
+#[constant]
+pub const MAX_NAME_LENGTH: usize = 32;
+
+/// Longest metadata URI accepted for assets and collections.
+#[constant]
+pub const MAX_URI_LENGTH: usize = 200;
+
+/// Maximum number of creators sharing the royalties of an asset or collection.
+#[constant]
+pub const MAX_CREATORS: usize = 5;
+
+/// Maximum number of programs in a royalties allow list or deny list.
+#[constant]
+pub const MAX_RULE_SET_PROGRAMS: usize = 10;
+
+/// Seed of this program's delegate PDA, which can be set as the authority of delegate plugins.
+#[constant]
+pub const DELEGATE_SEED: &str = "delegate";
+
+/// Longest message accepted in an autograph signature.
+#[constant]
+pub const MAX_AUTOGRAPH_MESSAGE_LENGTH: usize = 140;
+
+/// Seed of the PDA holding a pending collection update authority handover.
+#[constant]
+pub const UPDATE_AUTHORITY_PROPOSAL_SEED: &str = "update_authority_proposal";
+
+/// Seed of the mint config PDA of a program-controlled collection.
+#[constant]
+pub const MINT_CONFIG_SEED: &str = "mint_config";
+
+/// Maximum number of phases in a mint schedule.
+#[constant]
+pub const MAX_MINT_PHASES: usize = 5;
+
+/// Seed of the PDA counting the mints of a wallet in a collection.
+#[constant]
+pub const MINT_RECORD_SEED: &str = "mint_record";
+
+/// Placeholder replaced by the mint number in the name and URI templates of a mint config.
+#[constant]
+pub const MINT_NUMBER_PLACEHOLDER: &str = "{id}";
+
+/// Seed of the PDA marking a key asset as used in a holders mint phase.
+#[constant]
+pub const KEY_ASSET_RECORD_SEED: &str = "key_asset_record";
+
+/// Seed of the staking pool PDA of a collection, which is also the freeze delegate of staked assets.
+#[constant]
+pub const STAKE_POOL_SEED: &str = "stake_pool";
+
+/// Seed of the PDA recording a staked asset.
+#[constant]
+pub const STAKE_RECORD_SEED: &str = "stake_record";
+
+/// Seed of the token account holding the rewards of a staking pool.
+#[constant]
+pub const REWARD_VAULT_SEED: &str = "reward_vault";
+
+/// Seed of the PDA holding the rewards an owner was still owed when unstaking from a pool.
+#[constant]
+pub const PENDING_REWARDS_SEED: &str = "pending_rewards";
+
+/// Maximum number of attribute values with a reward multiplier in a staking pool.
+#[constant]
+pub const MAX_REWARD_MULTIPLIERS: usize = 8;
+
+/// Longest attribute key or value a staking pool can match on.
+#[constant]
+pub const MAX_ATTRIBUTE_LENGTH: usize = 32;
+
+/// Reward multiplier of assets without a matching attribute, in basis points (1x).
+#[constant]
+pub const BASE_REWARD_MULTIPLIER: u16 = 10_000;
+
+/// Seed of the listing PDA of an asset, which is also its transfer and freeze delegate while listed.
+#[constant]
+pub const LISTING_SEED: &str = "listing";
+
+/// Seed of the PDA escrowing the lamports of a collection offer.
+#[constant]
+pub const OFFER_SEED: &str = "offer";
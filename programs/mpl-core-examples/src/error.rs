@@ -2,6 +2,177 @@ use anchor_lang::prelude::*;
 
 #[error_code]
 pub enum ErrorCode {
-    #[msg("Custom error message")]
-    CustomError,
+    // Metadata
+    #[msg("The name can not be empty")]
+    EmptyName,
+    #[msg("The name is longer than the maximum allowed length")]
+    NameTooLong,
+    #[msg("The URI is longer than the maximum allowed length")]
+    UriTooLong,
+
+    // Royalties
+    #[msg("Royalty basis points can not exceed 10000")]
+    InvalidBasisPoints,
+    #[msg("Creator percentages must add up to 100")]
+    InvalidCreatorShares,
+    #[msg("The same creator address was listed more than once")]
+    DuplicateCreator,
+    #[msg("Too many creators were listed")]
+    TooManyCreators,
+    #[msg("A program allow list or deny list can not be empty")]
+    EmptyRuleSet,
+    #[msg("Too many programs were listed in the rule set")]
+    TooManyRuleSetPrograms,
+    #[msg("The same program was listed more than once in the rule set")]
+    DuplicateRuleSetProgram,
+    #[msg("The mint, token account and token program are required to pay in tokens")]
+    PaymentAccountsRequired,
+
+    // Plugins
+    #[msg("Too many plugins were requested")]
+    TooManyPlugins,
+    #[msg("The same plugin was requested more than once")]
+    DuplicatePlugin,
+    #[msg("This plugin can only be added to a collection")]
+    PluginNotSupportedOnAsset,
+    #[msg("This plugin can only be added to an asset")]
+    PluginNotSupportedOnCollection,
+    #[msg("The requested authority is not valid for this plugin")]
+    InvalidPluginAuthority,
+    #[msg("An oracle adapter needs at least one lifecycle check")]
+    MissingLifecycleChecks,
+    #[msg("The same lifecycle event was configured more than once")]
+    DuplicateLifecycleCheck,
+    #[msg("Oracle lifecycle checks can only have the can reject flag (4)")]
+    InvalidLifecycleCheckFlags,
+    #[msg("The plugin is not present on the account")]
+    PluginNotFound,
+
+    // Editions
+    #[msg("The collection does not have a MasterEdition plugin")]
+    MasterEditionPluginNotFound,
+    #[msg("All editions of this master edition have been minted")]
+    EditionSupplyExhausted,
+    #[msg("The max supply of a master edition must be greater than zero")]
+    InvalidMaxSupply,
+
+    // Minting
+    #[msg("The mint has not started yet")]
+    MintNotStarted,
+    #[msg("The mint has ended")]
+    MintEnded,
+    #[msg("The mint must end after it starts")]
+    InvalidMintWindow,
+    #[msg("A mint schedule needs at least one phase")]
+    EmptyMintSchedule,
+    #[msg("Too many mint phases were listed")]
+    TooManyMintPhases,
+    #[msg("Mint phases must be in order and can not overlap")]
+    OverlappingMintPhases,
+    #[msg("A mint phase that has started can not be changed")]
+    MintPhaseStarted,
+    #[msg("The supply cap of a mint must be greater than zero")]
+    InvalidMintSupply,
+    #[msg("Every asset of this mint has been minted")]
+    MintSoldOut,
+    #[msg("This wallet has reached its mint limit")]
+    WalletMintLimitReached,
+    #[msg("A mint record is required to mint into a program-controlled collection")]
+    MissingMintRecord,
+    #[msg("The mint record does not belong to this minter and collection")]
+    InvalidMintRecord,
+    #[msg("The treasury does not match the mint config")]
+    InvalidTreasury,
+    #[msg("The minter does not hold an asset of the required collection")]
+    NotAHolder,
+    #[msg("This key asset has already been used to mint")]
+    KeyAssetAlreadyUsed,
+    #[msg("The key asset record does not belong to this key asset and collection")]
+    InvalidKeyAssetRecord,
+
+    // Allowlist
+    #[msg("This collection has an allowlist, a proof is required")]
+    AllowlistProofRequired,
+    #[msg("The wallet and allocation are not on the allowlist")]
+    InvalidAllowlistProof,
+    #[msg("The collection does not have an allowlist")]
+    AllowlistNotSet,
+
+    // Staking
+    #[msg("The asset is frozen and can not be staked")]
+    AssetFrozen,
+    #[msg("Too many reward multipliers")]
+    TooManyRewardMultipliers,
+    #[msg("The reward multiplier attribute is too long")]
+    RewardAttributeTooLong,
+    #[msg("The reward mint, vault, token account and token program are required")]
+    RewardAccountsRequired,
+    #[msg("The reward account does not match the staking pool")]
+    InvalidRewardAccount,
+    #[msg("A pending rewards account is required to keep the rewards the vault can not pay")]
+    MissingPendingRewards,
+    #[msg("The pending rewards account does not belong to this owner and stake pool")]
+    InvalidPendingRewards,
+
+    // Marketplace
+    #[msg("The price of a listing must be greater than zero")]
+    InvalidListingPrice,
+    #[msg("The listing must expire in the future")]
+    InvalidListingExpiry,
+    #[msg("The listing has expired")]
+    ListingExpired,
+    #[msg("The seller does not match the listing")]
+    SellerMismatch,
+    #[msg("The creator accounts do not match the royalties of the asset")]
+    CreatorAccountMismatch,
+
+    // Offers
+    #[msg("The price of an offer must be greater than zero")]
+    InvalidOfferPrice,
+    #[msg("An offer must be for at least one asset")]
+    InvalidOfferQuantity,
+    #[msg("The offer must expire in the future")]
+    InvalidOfferExpiry,
+    #[msg("The offer has expired")]
+    OfferExpired,
+    #[msg("The offer has not expired yet")]
+    OfferNotExpired,
+
+    // Autographs
+    #[msg("The autograph message can not be empty")]
+    EmptyAutographMessage,
+    #[msg("The autograph message is longer than the maximum allowed length")]
+    AutographMessageTooLong,
+    #[msg("The autographer must sign the transaction")]
+    MissingAutographer,
+    #[msg("This address has already signed the asset")]
+    DuplicateAutograph,
+    #[msg("This address has not signed the asset")]
+    AutographNotFound,
+
+    // Accounts
+    #[msg("The account is not a valid mpl-core asset")]
+    InvalidAsset,
+    #[msg("The account is not a valid mpl-core collection")]
+    InvalidCollection,
+    #[msg("The asset belongs to a collection that was not provided")]
+    MissingCollection,
+    #[msg("The collection account does not match the collection of the asset")]
+    CollectionMismatch,
+    #[msg("The asset is already part of this collection")]
+    SameCollection,
+    #[msg("Either the current or the new collection of the asset must be provided")]
+    NoCollectionChange,
+    #[msg("The collection still has assets")]
+    CollectionNotEmpty,
+
+    // Authorities
+    #[msg("The signer is not allowed to perform this action")]
+    UnauthorizedSigner,
+    #[msg("Nobody is allowed to update this plugin")]
+    ImmutablePlugin,
+    #[msg("The new update authority must differ from the current one")]
+    SameUpdateAuthority,
+    #[msg("The collection authority does not match the collection config")]
+    InvalidCollectionAuthority,
 }
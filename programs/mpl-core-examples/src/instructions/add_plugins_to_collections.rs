@@ -4,12 +4,17 @@ use mpl_core::{
         AddCollectionExternalPluginAdapterV1CpiBuilder, AddCollectionPluginV1CpiBuilder,
     },
     types::{
-        BubblegumV2, Creator, ExternalCheckResult, ExternalPluginAdapterInitInfo,
-        HookableLifecycleEvent, OracleInitInfo, Royalties,
+        BubblegumV2, ExternalCheckResult, ExternalPluginAdapterInitInfo, HookableLifecycleEvent,
+        OracleInitInfo, Royalties,
     },
 };
 
-use crate::{AddRoyaltiesPluginArgs, ONCHAIN_METAPLEX_ORACLE_PLUGIN};
+use crate::{
+    royalties::{to_creators, validate_royalties, validate_rule_set},
+    state::CollectionConfig,
+    validation::{invoke_as_collection_authority, load_collection},
+    AddRoyaltiesPluginArgs, ONCHAIN_METAPLEX_ORACLE_PLUGIN,
+};
 
 /// For some plugins. it better to add them at the collection-level because it is more rent efficient
 /// I'll take some examples here
@@ -27,6 +32,14 @@ pub struct AddPluginsToCollections<'info> {
     /// The owner or delegate of the asset.
     pub update_authority: Option<Signer<'info>>,
 
+    /// The config of a program-controlled collection, see `create_program_collection`.
+    /// When present, `update_authority` must be the config authority and the collection authority PDA signs.
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+
+    /// The collection authority PDA of `collection_config`.
+    /// CHECK: Checked against the collection config.
+    pub collection_authority: Option<AccountInfo<'info>>,
+
     /// The system program.
     pub system_program: Program<'info, System>,
 
@@ -56,15 +69,22 @@ impl<'info> AddPluginsToCollections<'info> {
     /// - Any scenario where collection-level compression enables massive
     ///   asset issuance without high on-chain storage costs.
     pub fn add_bubblegum_plugin_to_collection(&mut self) -> Result<()> {
-        AddCollectionPluginV1CpiBuilder::new(&self.mpl_core)
-            .payer(self.payer.to_account_info().as_ref())
-            .system_program(self.system_program.to_account_info().as_ref())
+        load_collection(&self.collection)?;
+
+        let mut builder = AddCollectionPluginV1CpiBuilder::new(&self.mpl_core);
+        builder
+            .payer(self.payer.as_ref())
+            .system_program(self.system_program.as_ref())
             .collection(self.collection.as_ref())
-            .authority(self.update_authority.as_deref())
             .log_wrapper(self.log_wrapper.as_ref())
-            .plugin(mpl_core::types::Plugin::BubblegumV2(BubblegumV2 {}))
-            .invoke()?;
-        Ok(())
+            .plugin(mpl_core::types::Plugin::BubblegumV2(BubblegumV2 {}));
+        invoke_as_collection_authority(
+            self.collection_config.as_ref(),
+            Some(&self.collection),
+            self.update_authority.as_deref(),
+            self.collection_authority.as_ref(),
+            |authority, signer_seeds| builder.authority(authority).invoke_signed(signer_seeds),
+        )
     }
 
     /// Adds a royalties plugin to an asset or collection.
@@ -81,37 +101,47 @@ impl<'info> AddPluginsToCollections<'info> {
     /// * `args` - An [`AddRoyaltiesPluginArgs`] struct containing:
     ///   - `basis_points`: The royalty percentage in basis points (1% = 100 bps).  
     ///   - `creators`: A vector of creator structs, each specifying an address and percentage share.
+    ///   - `rule_set`: Optional list of programs allowed (or not) to transfer the assets, none by default.
+    ///   - `delegate`: Optional plugin authority, e.g. this program's delegate PDA.
     pub fn add_royalties_plugin_to_collection(
         &mut self,
         args: AddRoyaltiesPluginArgs,
     ) -> Result<()> {
-        // Map the incoming creators into the expected `Creator` format.
-        let creators = args
-            .creators
-            .into_iter()
-            .map(|c| Creator {
-                address: c.address,
-                percentage: c.percentage,
-            })
-            .collect();
-        AddCollectionPluginV1CpiBuilder::new(&self.mpl_core)
-            .payer(self.payer.to_account_info().as_ref())
-            .system_program(self.system_program.to_account_info().as_ref())
+        let rule_set = args.rule_set.unwrap_or_default();
+        validate_royalties(args.basis_points, &args.creators)?;
+        validate_rule_set(&rule_set)?;
+        load_collection(&self.collection)?;
+
+        let creators = to_creators(args.creators);
+        let mut builder = AddCollectionPluginV1CpiBuilder::new(&self.mpl_core);
+        builder
+            .payer(self.payer.as_ref())
+            .system_program(self.system_program.as_ref())
             .collection(self.collection.as_ref())
-            .authority(self.update_authority.as_deref())
             .log_wrapper(self.log_wrapper.as_ref())
             .plugin(mpl_core::types::Plugin::Royalties(Royalties {
                 creators,
                 basis_points: args.basis_points,
-                rule_set: mpl_core::types::RuleSet::None,
-            }))
-            .invoke()?;
-        Ok(())
+                rule_set: rule_set.into(),
+            }));
+        if let Some(delegate) = args.delegate {
+            builder.init_authority(delegate.into());
+        }
+        invoke_as_collection_authority(
+            self.collection_config.as_ref(),
+            Some(&self.collection),
+            self.update_authority.as_deref(),
+            self.collection_authority.as_ref(),
+            |authority, signer_seeds| builder.authority(authority).invoke_signed(signer_seeds),
+        )
     }
 
     pub fn add_metaplex_oracle_to_collection(&mut self) -> Result<()> {
-        AddCollectionExternalPluginAdapterV1CpiBuilder::new(&self.mpl_core)
-            .payer(self.payer.to_account_info().as_ref())
+        load_collection(&self.collection)?;
+
+        let mut builder = AddCollectionExternalPluginAdapterV1CpiBuilder::new(&self.mpl_core);
+        builder
+            .payer(self.payer.as_ref())
             .collection(self.collection.as_ref())
             .init_info(ExternalPluginAdapterInitInfo::Oracle(OracleInitInfo {
                 base_address: ONCHAIN_METAPLEX_ORACLE_PLUGIN,
@@ -122,8 +152,13 @@ impl<'info> AddPluginsToCollections<'info> {
                 )],
                 base_address_config: None,
                 results_offset: Some(mpl_core::types::ValidationResultsOffset::Anchor),
-            }))
-            .invoke()?;
-        Ok(())
+            }));
+        invoke_as_collection_authority(
+            self.collection_config.as_ref(),
+            Some(&self.collection),
+            self.update_authority.as_deref(),
+            self.collection_authority.as_ref(),
+            |authority, signer_seeds| builder.authority(authority).invoke_signed(signer_seeds),
+        )
     }
 }
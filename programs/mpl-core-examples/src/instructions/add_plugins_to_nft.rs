@@ -2,13 +2,17 @@ use anchor_lang::prelude::*;
 use mpl_core::{
     instructions::{AddExternalPluginAdapterV1CpiBuilder, AddPluginV1CpiBuilder},
     types::{
-        Autograph, AutographSignature, BurnDelegate, Creator, ExternalCheckResult,
+        Autograph, AutographSignature, BurnDelegate, ExternalCheckResult,
         ExternalPluginAdapterInitInfo, FreezeDelegate, HookableLifecycleEvent, OracleInitInfo,
         Plugin, Royalties, TransferDelegate,
     },
 };
 
-use crate::{MPL_CORE_ID, ONCHAIN_METAPLEX_ORACLE_PLUGIN, SPL_NOOP_PROGRAM};
+use crate::{
+    royalties::{to_creators, validate_royalties, validate_rule_set, RuleSetArgs},
+    validation::{validate_asset_collection, validate_autograph_message},
+    DelegateArgs, MPL_CORE_ID, ONCHAIN_METAPLEX_ORACLE_PLUGIN, SPL_NOOP_PROGRAM,
+};
 
 #[derive(Accounts)]
 pub struct AddPluginsToNft<'info> {
@@ -45,7 +49,7 @@ pub struct AddPluginsToNft<'info> {
     pub mpl_core: AccountInfo<'info>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct CreatorArgs {
     pub address: Pubkey,
     pub percentage: u8,
@@ -55,6 +59,16 @@ pub struct CreatorArgs {
 pub struct AddRoyaltiesPluginArgs {
     pub basis_points: u16,
     pub creators: Vec<CreatorArgs>,
+    /// Which programs can transfer the asset. Defaults to `RuleSetArgs::None`.
+    pub rule_set: Option<RuleSetArgs>,
+    /// Optional authority of the plugin. Defaults to the update authority.
+    pub delegate: Option<DelegateArgs>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AddDelegatePluginArgs {
+    /// Optional delegate of the plugin. Defaults to the owner of the asset.
+    pub delegate: Option<DelegateArgs>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -77,37 +91,35 @@ impl<'info> AddPluginsToNft<'info> {
     /// * `args` - An [`AddRoyaltiesPluginArgs`] struct containing:
     ///   - `basis_points`: The royalty percentage in basis points (1% = 100 bps).  
     ///   - `creators`: A vector of creator structs, each specifying an address and percentage share.
+    ///   - `rule_set`: Optional list of programs allowed (or not) to transfer the asset, none by default.
+    ///   - `delegate`: Optional plugin authority, e.g. this program's delegate PDA.
     pub fn add_royalties_plugin(&mut self, args: AddRoyaltiesPluginArgs) -> Result<()> {
-        // Map the incoming creators into the expected `Creator` format.
-        let creators = args
-            .creators
-            .into_iter()
-            .map(|c| Creator {
-                address: c.address,
-                percentage: c.percentage,
-            })
-            .collect();
-
-        // Build and invoke the CPI to add the royalties plugin.
-        AddPluginV1CpiBuilder::new(&self.mpl_core)
-            .asset(self.asset.as_ref())
-            .collection(self.collection.as_ref())
-            .payer(self.payer.to_account_info().as_ref())
-            .system_program(self.system_program.to_account_info().as_ref())
-            .authority(self.authority.as_deref())
-            .plugin(Plugin::Royalties(Royalties {
+        let rule_set = args.rule_set.unwrap_or_default();
+        validate_royalties(args.basis_points, &args.creators)?;
+        validate_rule_set(&rule_set)?;
+
+        let creators = to_creators(args.creators);
+        self.add_plugin(
+            Plugin::Royalties(Royalties {
                 basis_points: args.basis_points,
                 creators,
-                rule_set: mpl_core::types::RuleSet::None,
-            }))
-            .invoke()?;
-        Ok(())
+                rule_set: rule_set.into(),
+            }),
+            args.delegate,
+        )
     }
 
     /// Adds an "Autograph" plugin to the asset.
     /// This plugin allows recording of digital signatures/messages
     /// from authorities (e.g. creator autographing their NFT).
+    /// The first signature belongs to the signer adding the plugin.
     pub fn add_autograph_plugin_to_asset(&mut self, args: AddAutographPluginArgs) -> Result<()> {
+        validate_autograph_message(&args.message)?;
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
+        // mpl-core falls back to the payer when no authority is passed
+        let autographer = self.authority.as_ref().unwrap_or(&self.payer).key();
+
         AddPluginV1CpiBuilder::new(&self.mpl_core)
             .asset(self.asset.as_ref())
             .collection(self.collection.as_ref())
@@ -117,11 +129,7 @@ impl<'info> AddPluginsToNft<'info> {
             .plugin(Plugin::Autograph(Autograph {
                 signatures: vec![AutographSignature {
                     message: args.message,
-                    address: self
-                        .authority
-                        .as_ref()
-                        .map(|auth| auth.key())
-                        .unwrap_or_default(),
+                    address: autographer,
                 }],
             }))
             .invoke()?;
@@ -131,7 +139,7 @@ impl<'info> AddPluginsToNft<'info> {
     /// Adds a Transfer Delegate plugin.
     ///  An **Owner-Managed** plugin that allows a designated delegate to transfer
     /// the asset on behalf of the owner. Useful for cases such as:
-    /// - Escrowless sales: transfer NFTs directly to buyers without escrow.
+    /// - Escrowless sales: transfer NFTs directly to buyers without escrow (see `buy`).
     /// - Gaming: automatically move assets when in-game events occur.
     /// - Subscriptions: transfer NFTs as part of recurring service logic.
     ///
@@ -140,16 +148,11 @@ impl<'info> AddPluginsToNft<'info> {
     /// whenever the asset is transferred, meaning delegates do not persist
     /// across owners. This prevents infinite delegation chains and ensures
     /// clean ownership semantics.
-    pub fn add_transfer_delegate_plugin_to_asset(&mut self) -> Result<()> {
-        AddPluginV1CpiBuilder::new(&self.mpl_core)
-            .asset(self.asset.as_ref())
-            .collection(self.collection.as_ref())
-            .payer(self.payer.to_account_info().as_ref())
-            .system_program(self.system_program.to_account_info().as_ref())
-            .authority(self.authority.as_deref())
-            .plugin(Plugin::TransferDelegate(TransferDelegate {}))
-            .invoke()?;
-        Ok(())
+    pub fn add_transfer_delegate_plugin_to_asset(
+        &mut self,
+        args: AddDelegatePluginArgs,
+    ) -> Result<()> {
+        self.add_plugin(Plugin::TransferDelegate(TransferDelegate {}), args.delegate)
     }
 
     /// Freeze Plugin
@@ -159,8 +162,8 @@ impl<'info> AddPluginsToNft<'info> {
     /// unfreeze the asset or revoke their authority at any time.
     ///
     /// Common use cases:
-    /// - Escrowless staking: freeze NFTs while staked without transferring to escrow.
-    /// - Escrowless marketplace listings: list NFTs without moving them to marketplace custody.
+    /// - Escrowless staking: freeze NFTs while staked without transferring to escrow (see `stake`).
+    /// - Escrowless marketplace listings: list NFTs without moving them to marketplace custody (see `list`).
     /// - Gaming: temporarily lock in-game items during active gameplay.
     /// - Rentals: freeze NFTs while rented out to ensure non-transferability.
     /// - Governance: lock governance tokens during voting or proposal participation.
@@ -172,16 +175,14 @@ impl<'info> AddPluginsToNft<'info> {
     /// strong guarantees for protocols that require immovable assets, but
     /// requires the plugin authority to explicitly unfreeze before transfers
     /// are possible.
-    pub fn add_freeze_delegate_plugin_to_asset(&mut self) -> Result<()> {
-        AddPluginV1CpiBuilder::new(&self.mpl_core)
-            .asset(self.asset.as_ref())
-            .collection(self.collection.as_ref())
-            .payer(self.payer.to_account_info().as_ref())
-            .system_program(self.system_program.to_account_info().as_ref())
-            .authority(self.authority.as_deref())
-            .plugin(Plugin::FreezeDelegate(FreezeDelegate { frozen: true }))
-            .invoke()?;
-        Ok(())
+    pub fn add_freeze_delegate_plugin_to_asset(
+        &mut self,
+        args: AddDelegatePluginArgs,
+    ) -> Result<()> {
+        self.add_plugin(
+            Plugin::FreezeDelegate(FreezeDelegate { frozen: true }),
+            args.delegate,
+        )
     }
 
     /// Burn Plugin
@@ -202,19 +203,13 @@ impl<'info> AddPluginsToNft<'info> {
     /// ⚠️ Warning:
     /// Burn is **irreversible** — once executed, the asset is destroyed and
     /// cannot be recovered.
-    pub fn add_burn_delegate_plugin_to_asset(&mut self) -> Result<()> {
-        AddPluginV1CpiBuilder::new(&self.mpl_core)
-            .asset(self.asset.as_ref())
-            .collection(self.collection.as_ref())
-            .payer(self.payer.to_account_info().as_ref())
-            .system_program(self.system_program.to_account_info().as_ref())
-            .authority(self.authority.as_deref())
-            .plugin(Plugin::BurnDelegate(BurnDelegate {}))
-            .invoke()?;
-        Ok(())
+    pub fn add_burn_delegate_plugin_to_asset(&mut self, args: AddDelegatePluginArgs) -> Result<()> {
+        self.add_plugin(Plugin::BurnDelegate(BurnDelegate {}), args.delegate)
     }
 
     pub fn add_metaplex_oracle_to_nft(&mut self) -> Result<()> {
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
         AddExternalPluginAdapterV1CpiBuilder::new(&self.mpl_core)
             .asset(self.asset.as_ref())
             .collection(self.collection.as_ref())
@@ -234,4 +229,24 @@ impl<'info> AddPluginsToNft<'info> {
             .invoke()?;
         Ok(())
     }
+
+    /// Adds `plugin` to the asset. When a delegate is given it becomes the plugin authority,
+    /// otherwise mpl-core falls back to the default authority of the plugin.
+    fn add_plugin(&self, plugin: Plugin, delegate: Option<DelegateArgs>) -> Result<()> {
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
+        let mut builder = AddPluginV1CpiBuilder::new(&self.mpl_core);
+        builder
+            .asset(self.asset.as_ref())
+            .collection(self.collection.as_ref())
+            .payer(self.payer.as_ref())
+            .system_program(self.system_program.as_ref())
+            .authority(self.authority.as_deref())
+            .plugin(plugin);
+        if let Some(delegate) = delegate {
+            builder.init_authority(delegate.into());
+        }
+        builder.invoke()?;
+        Ok(())
+    }
 }
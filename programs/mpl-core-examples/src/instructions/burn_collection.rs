@@ -0,0 +1,136 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use mpl_core::instructions::BurnCollectionV1CpiBuilder;
+
+use crate::{
+    error::ErrorCode,
+    state::{CollectionConfig, EditionCouter, MintConfig},
+    validation::{load_collection, validate_program_collection},
+    SPL_NOOP_PROGRAM,
+};
+
+#[derive(Accounts)]
+pub struct BurnCollection<'info> {
+    /// Pays for the transaction.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: The collection to be burned, checked in mpl-core
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+
+    /// The update authority of the collection.
+    /// mpl-core sends it the rent of the collection, which is forwarded to the recipient.
+    #[account(mut)]
+    pub authority: Option<Signer<'info>>,
+
+    /// Receives the rent of the collection and of the program accounts tied to it.
+    /// CHECK: Only receives lamports.
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+
+    /// The edition counter of the collection, closed along with it.
+    /// It only exists if editions were minted, and has to be passed then to get its rent back.
+    #[account(
+        mut,
+        close = recipient,
+        seeds=[b"edition_counter", collection.key().as_ref()],
+        bump
+    )]
+    pub edition_counter: Option<Account<'info, EditionCouter>>,
+
+    /// The config of a program-controlled collection, closed along with the collection.
+    /// `authority` must then be the config authority.
+    #[account(mut, close = recipient, has_one = collection @ ErrorCode::CollectionMismatch)]
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+
+    /// The mint config of a program-controlled collection, closed along with the collection.
+    #[account(mut, close = recipient, has_one = collection @ ErrorCode::CollectionMismatch)]
+    pub mint_config: Option<Account<'info, MintConfig>>,
+
+    /// The collection authority PDA of `collection_config`, which signs the burn.
+    /// CHECK: Checked against the collection config.
+    #[account(mut)]
+    pub collection_authority: Option<AccountInfo<'info>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// CHECK: Checked in the address constraint
+    #[account(address = mpl_core::ID)]
+    pub mpl_core_program: AccountInfo<'info>,
+}
+
+impl<'info> BurnCollection<'info> {
+    /// Burns an empty collection and sends its rent to the recipient, along with the rent of
+    /// the edition counter and the collection and mint configs closed by the `close` constraint.
+    pub fn burn_collection(&mut self) -> Result<()> {
+        let collection = load_collection(&self.collection)?;
+        require!(collection.current_size == 0, ErrorCode::CollectionNotEmpty);
+
+        let mut builder = BurnCollectionV1CpiBuilder::new(&self.mpl_core_program);
+        builder
+            .collection(&self.collection)
+            .payer(self.payer.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref());
+
+        // mpl-core pays the rent of the collection out to the authority, or the payer without one
+        let Some(config) = &self.collection_config else {
+            let authority = self
+                .authority
+                .as_ref()
+                .map_or(self.payer.as_ref(), |authority| authority.as_ref());
+            let lamports = authority.lamports();
+            builder.authority(self.authority.as_deref()).invoke()?;
+            return self.forward_rent(authority, authority.lamports() - lamports, &[]);
+        };
+        config.validate_authority(self.authority.as_ref().map(|authority| authority.key))?;
+        let collection_authority = validate_program_collection(
+            config,
+            Some(&self.collection),
+            self.collection_authority.as_ref(),
+        )?;
+
+        let config_key = config.key();
+        let bump = [config.collection_authority_bump];
+        let signer_seeds = CollectionConfig::collection_authority_seeds(&config_key, &bump);
+        let lamports = collection_authority.lamports();
+        builder
+            .authority(Some(collection_authority))
+            .invoke_signed(&[&signer_seeds])?;
+        self.forward_rent(
+            collection_authority,
+            collection_authority.lamports() - lamports,
+            &[&signer_seeds],
+        )
+    }
+
+    /// Moves the rent mpl-core sent to `from` on to the recipient.
+    fn forward_rent(
+        &self,
+        from: &AccountInfo<'info>,
+        rent: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        if rent == 0 || from.key() == self.recipient.key() {
+            return Ok(());
+        }
+        transfer(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: from.clone(),
+                    to: self.recipient.clone(),
+                },
+                signer_seeds,
+            ),
+            rent,
+        )
+    }
+}
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use mpl_core::instructions::BurnV1CpiBuilder;
 
-use crate::MPL_CORE_ID;
+use crate::{validation::validate_asset_collection, MPL_CORE_ID};
 
 #[derive(Accounts)]
 pub struct BurnNFT<'info> {
@@ -28,6 +28,8 @@ pub struct BurnNFT<'info> {
 
 impl<'info> BurnNFT<'info> {
     pub fn burn_nft(&mut self) -> Result<()> {
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
         BurnV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
             .payer(self.payer.to_account_info().as_ref())
             .asset(self.asset.to_account_info().as_ref())
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, state::CollectionConfig};
+
+/// Changes the mint rules a program-controlled collection applies in every mint instruction.
+#[derive(Accounts)]
+pub struct UpdateCollectionConfig<'info> {
+    /// The authority of the collection config.
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = authority @ ErrorCode::UnauthorizedSigner)]
+    pub collection_config: Account<'info, CollectionConfig>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetMintLimitArgs {
+    /// `None` removes the limit.
+    pub max_per_wallet: Option<u32>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetAllowlistRootArgs {
+    /// `None` removes the allowlist and opens minting to every wallet.
+    pub root: Option<[u8; 32]>,
+}
+
+impl<'info> UpdateCollectionConfig<'info> {
+    pub fn set_mint_limit(&mut self, args: SetMintLimitArgs) -> Result<()> {
+        self.collection_config.max_per_wallet = args.max_per_wallet;
+        Ok(())
+    }
+
+    /// Replaces the allowlist. Mints already counted in the current phase still count
+    /// towards the allocation of the new list.
+    pub fn set_allowlist_root(&mut self, args: SetAllowlistRootArgs) -> Result<()> {
+        self.collection_config.allowlist_root = args.root;
+        Ok(())
+    }
+
+    /// Starts a new mint phase, so every wallet can mint up to the limit again.
+    /// Mint records are only reset when they are next used.
+    pub fn start_mint_phase(&mut self) -> Result<()> {
+        self.collection_config.phase = self
+            .collection_config
+            .phase
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
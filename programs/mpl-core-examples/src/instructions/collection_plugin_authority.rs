@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use mpl_core::{
+    fetch_collection_plugin,
+    instructions::{
+        ApproveCollectionPluginAuthorityV1CpiBuilder, RevokeCollectionPluginAuthorityV1CpiBuilder,
+    },
+    types::PluginType,
+};
+
+use crate::{
+    error::ErrorCode,
+    state::CollectionConfig,
+    validation::{invoke_as_collection_authority, load_collection},
+    ApprovePluginAuthorityArgs, RevokePluginAuthorityArgs, SPL_NOOP_PROGRAM,
+};
+
+/// Same as [`crate::NFTPluginAuthority`] for the plugins of a collection,
+/// e.g. handing a permanent freeze delegate to a staking program.
+#[derive(Accounts)]
+pub struct CollectionPluginAuthority<'info> {
+    /// The address of the collection.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+
+    /// The account paying for the storage fees.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The current authority of the plugin, usually the update authority.
+    pub authority: Option<Signer<'info>>,
+
+    /// The config of a program-controlled collection, see `create_program_collection`.
+    /// When present, `authority` must be the config authority and the collection authority PDA signs.
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+
+    /// The collection authority PDA of `collection_config`.
+    /// CHECK: Checked against the collection config.
+    pub collection_authority: Option<AccountInfo<'info>>,
+
+    /// The system program.
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+impl<'info> CollectionPluginAuthority<'info> {
+    /// Sets `new_authority` as the authority of a plugin already on the collection.
+    pub fn approve_plugin_authority(&mut self, args: ApprovePluginAuthorityArgs) -> Result<()> {
+        load_collection(&self.collection)?;
+
+        require!(
+            !args.plugin_type.is_owner_managed(),
+            ErrorCode::PluginNotSupportedOnCollection
+        );
+
+        let plugin_type = PluginType::from(args.plugin_type);
+        fetch_collection_plugin::<()>(self.collection.as_ref(), plugin_type.clone())
+            .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        let mut builder = ApproveCollectionPluginAuthorityV1CpiBuilder::new(&self.mpl_core);
+        builder
+            .collection(self.collection.as_ref())
+            .payer(self.payer.as_ref())
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin_type(plugin_type)
+            .new_authority(args.new_authority.into());
+        invoke_as_collection_authority(
+            self.collection_config.as_ref(),
+            Some(&self.collection),
+            self.authority.as_deref(),
+            self.collection_authority.as_ref(),
+            |authority, signer_seeds| builder.authority(authority).invoke_signed(signer_seeds),
+        )
+    }
+
+    /// Gives the authority of a collection plugin back to the update authority.
+    pub fn revoke_plugin_authority(&mut self, args: RevokePluginAuthorityArgs) -> Result<()> {
+        load_collection(&self.collection)?;
+
+        let plugin_type = PluginType::from(args.plugin_type);
+        fetch_collection_plugin::<()>(self.collection.as_ref(), plugin_type.clone())
+            .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        let mut builder = RevokeCollectionPluginAuthorityV1CpiBuilder::new(&self.mpl_core);
+        builder
+            .collection(self.collection.as_ref())
+            .payer(self.payer.as_ref())
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin_type(plugin_type);
+        invoke_as_collection_authority(
+            self.collection_config.as_ref(),
+            Some(&self.collection),
+            self.authority.as_deref(),
+            self.collection_authority.as_ref(),
+            |authority, signer_seeds| builder.authority(authority).invoke_signed(signer_seeds),
+        )
+    }
+}
@@ -8,7 +8,13 @@ use mpl_core::{
     },
 };
 
-use crate::{MPL_CORE_ID, ONCHAIN_METAPLEX_ORACLE_PLUGIN};
+use crate::{
+    build_external_plugin_adapters, build_plugin_authority_pairs,
+    error::ErrorCode,
+    validation::{validate_metadata, validate_optional_metadata},
+    ExternalPluginAdapterSpec, PluginSpec, PluginTarget, MPL_CORE_ID,
+    ONCHAIN_METAPLEX_ORACLE_PLUGIN,
+};
 
 /// Accounts required for creating a new collection or a collection with plugins.
 #[derive(Accounts)]
@@ -55,12 +61,28 @@ pub struct CreateMasterEditionArgs {
     /// Optional URI for the Master Edition plugin.
     pub master_edition_uri: Option<String>,
     /// Maximum number of editions allowed.
+    /// Open editions are created through `create_collection_with_plugins`.
     pub max_supply: u32,
 }
 
+/// Arguments for creating a collection with any combination of plugins and external plugin adapters.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateCollectionWithPluginsArgs {
+    /// Human-readable name of the collection.
+    pub name: String,
+    /// Metadata URI describing the collection.
+    pub uri: String,
+    /// Built-in plugins, each with the authority it should be created with.
+    pub plugins: Vec<PluginSpec>,
+    /// External plugin adapters (oracles, app data...), each with its own authority.
+    pub external_plugin_adapters: Vec<ExternalPluginAdapterSpec>,
+}
+
 impl<'info> CreateCollection<'info> {
     /// Creates a basic collection with a name and URI.
     pub fn create_collection(&mut self, args: CreateCollectionArgs) -> Result<()> {
+        validate_metadata(&args.name, &args.uri)?;
+
         CreateCollectionV1CpiBuilder::new(&self.mpl_core_program)
             .collection(self.collection.to_account_info().as_ref())
             .update_authority(self.update_authority.as_ref())
@@ -74,6 +96,16 @@ impl<'info> CreateCollection<'info> {
 
     /// Creates a master edition collection with a `MasterEdition` plugin.
     pub fn create_master_edition(&mut self, args: CreateMasterEditionArgs) -> Result<()> {
+        validate_metadata(
+            &args.name_of_master_edition_collection,
+            &args.uri_of_master_edition_collection,
+        )?;
+        require!(args.max_supply > 0, ErrorCode::InvalidMaxSupply);
+        validate_optional_metadata(
+            args.master_edition_name.as_deref(),
+            args.master_edition_uri.as_deref(),
+        )?;
+
         CreateCollectionV1CpiBuilder::new(&self.mpl_core_program)
             .collection(self.collection.to_account_info().as_ref())
             .update_authority(self.update_authority.as_ref())
@@ -98,6 +130,8 @@ impl<'info> CreateCollection<'info> {
         &mut self,
         args: CreateCollectionArgs,
     ) -> Result<()> {
+        validate_metadata(&args.name, &args.uri)?;
+
         CreateCollectionV1CpiBuilder::new(&self.mpl_core_program)
             .collection(self.collection.to_account_info().as_ref())
             .update_authority(self.update_authority.as_ref())
@@ -119,6 +153,8 @@ impl<'info> CreateCollection<'info> {
         &mut self,
         args: CreateCollectionArgs,
     ) -> Result<()> {
+        validate_metadata(&args.name, &args.uri)?;
+
         CreateCollectionV1CpiBuilder::new(&self.mpl_core_program)
             .collection(self.collection.to_account_info().as_ref())
             .update_authority(self.update_authority.as_ref())
@@ -139,6 +175,8 @@ impl<'info> CreateCollection<'info> {
         &mut self,
         args: CreateCollectionArgs,
     ) -> Result<()> {
+        validate_metadata(&args.name, &args.uri)?;
+
         CreateCollectionV1CpiBuilder::new(&self.mpl_core_program)
             .collection(self.collection.to_account_info().as_ref())
             .update_authority(self.update_authority.as_ref())
@@ -160,6 +198,8 @@ impl<'info> CreateCollection<'info> {
         &mut self,
         args: CreateCollectionArgs,
     ) -> Result<()> {
+        validate_metadata(&args.name, &args.uri)?;
+
         CreateCollectionV2CpiBuilder::new(&self.mpl_core_program)
             .collection(self.collection.to_account_info().as_ref())
             .update_authority(self.update_authority.as_ref())
@@ -182,4 +222,36 @@ impl<'info> CreateCollection<'info> {
             .invoke()?;
         Ok(())
     }
+
+    /// Creates a collection with several plugins and external plugin adapters in one CPI,
+    /// e.g. royalties + permanent freeze delegate + oracle.
+    /// The whole combination is validated before anything is sent to mpl-core.
+    pub fn create_collection_with_plugins(
+        &mut self,
+        args: CreateCollectionWithPluginsArgs,
+    ) -> Result<()> {
+        validate_metadata(&args.name, &args.uri)?;
+        let plugins = build_plugin_authority_pairs(args.plugins, PluginTarget::Collection)?;
+        let external_plugin_adapters = build_external_plugin_adapters(
+            args.external_plugin_adapters,
+            PluginTarget::Collection,
+        )?;
+
+        let mut builder = CreateCollectionV2CpiBuilder::new(&self.mpl_core_program);
+        builder
+            .collection(self.collection.as_ref())
+            .update_authority(self.update_authority.as_ref())
+            .system_program(self.system_program.as_ref())
+            .payer(self.payer.as_ref())
+            .uri(args.uri)
+            .name(args.name);
+        if !plugins.is_empty() {
+            builder.plugins(plugins);
+        }
+        if !external_plugin_adapters.is_empty() {
+            builder.external_plugin_adapters(external_plugin_adapters);
+        }
+        builder.invoke()?;
+        Ok(())
+    }
 }
@@ -1,11 +1,16 @@
 use anchor_lang::prelude::*;
 use mpl_core::{
-    fetch_asset_plugin,
+    fetch_collection_plugin,
     instructions::CreateV1CpiBuilder,
-    types::{Edition, Plugin, PluginAuthority, PluginAuthorityPair, PluginType},
+    types::{Edition, MasterEdition, Plugin, PluginAuthority, PluginAuthorityPair, PluginType},
 };
 
-use crate::state::EditionCouter;
+use crate::{
+    error::ErrorCode,
+    merkle::AllowlistProof,
+    state::{CollectionConfig, EditionCouter, MintRecord},
+    validation::{validate_metadata, validate_program_collection, validate_program_mint},
+};
 
 #[derive(Accounts)]
 pub struct CreateEdition<'info> {
@@ -38,6 +43,20 @@ pub struct CreateEdition<'info> {
     )]
     pub edition_count: Account<'info, EditionCouter>,
 
+    /// The config of a program-controlled master edition collection.
+    /// When present, `authority` must be the config authority, or the owner must sign when minting
+    /// from the allowlist, and the collection authority PDA signs.
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+
+    /// The collection authority PDA of `collection_config`.
+    /// CHECK: Checked against the collection config.
+    pub collection_authority: Option<AccountInfo<'info>>,
+
+    /// The mint record of the owner in the collection of `collection_config`, created on its first mint.
+    /// CHECK: The address is checked and the account created in `MintRecord::track`.
+    #[account(mut)]
+    pub mint_record: Option<AccountInfo<'info>>,
+
     pub system_program: Program<'info, System>,
 
     /// CHECK: It is checked in the address constraint
@@ -52,19 +71,61 @@ pub struct CreateEditionArgs {
 }
 
 impl<'info> CreateEdition<'info> {
+    /// Mints the next numbered edition of the master edition collection.
+    /// The mint is rejected once `max_supply` editions have been minted,
+    /// a `max_supply` of `None` makes it an open edition.
     pub fn create_edition(&mut self, create_edition_args: CreateEditionArgs) -> Result<()> {
+        self.create(create_edition_args, None)
+    }
+
+    /// Mints the next edition of a program-controlled master edition with an allowlist.
+    /// The listed owner signs for itself, and can receive up to its allocation per phase.
+    pub fn create_edition_from_allowlist(
+        &mut self,
+        create_edition_args: CreateEditionArgs,
+        proof: AllowlistProof,
+    ) -> Result<()> {
+        self.create(create_edition_args, Some(proof))
+    }
+
+    fn create(
+        &mut self,
+        create_edition_args: CreateEditionArgs,
+        allowlist: Option<AllowlistProof>,
+    ) -> Result<()> {
+        validate_metadata(
+            &create_edition_args.name_of_edition_asset,
+            &create_edition_args.uri_of_edition_asset,
+        )?;
+
+        let (_, master_edition, _) = fetch_collection_plugin::<MasterEdition>(
+            &self.master_edition_collection,
+            PluginType::MasterEdition,
+        )
+        .map_err(|_| error!(ErrorCode::MasterEditionPluginNotFound))?;
+
         let counter = &mut self.edition_count;
-        counter.edition_count += 1;
+        if let Some(max_supply) = master_edition.max_supply {
+            require!(
+                counter.edition_count < max_supply,
+                ErrorCode::EditionSupplyExhausted
+            );
+        }
+        counter.edition_count = counter
+            .edition_count
+            .checked_add(1)
+            .ok_or(ErrorCode::EditionSupplyExhausted)?;
 
         let edition_number = counter.edition_count;
 
-        CreateV1CpiBuilder::new(&self.mpl_core_program)
+        let mut builder = CreateV1CpiBuilder::new(&self.mpl_core_program);
+        builder
             .collection(Some(self.master_edition_collection.as_ref()))
             .asset(self.edition_asset.as_ref())
-          
-            .system_program(self.system_program.to_account_info().as_ref())
+            .authority(self.authority.as_deref())
+            .system_program(self.system_program.as_ref())
             .owner(self.owner.as_ref())
-            .payer(self.payer.to_account_info().as_ref())
+            .payer(self.payer.as_ref())
             .uri(create_edition_args.uri_of_edition_asset)
             .name(create_edition_args.name_of_edition_asset)
             .plugins(vec![PluginAuthorityPair {
@@ -72,9 +133,46 @@ impl<'info> CreateEdition<'info> {
                     number: edition_number,
                 }),
                 authority: Some(PluginAuthority::UpdateAuthority),
-            }])
-            .invoke()?;
+            }]);
+
+        let Some(config) = &self.collection_config else {
+            require!(allowlist.is_none(), ErrorCode::AllowlistNotSet);
+            builder.invoke()?;
+            return Ok(());
+        };
+        let collection_authority = validate_program_collection(
+            config,
+            Some(&self.master_edition_collection),
+            self.collection_authority.as_ref(),
+        )?;
+        let (owner, allocation) = validate_program_mint(
+            config,
+            self.authority.as_deref(),
+            self.owner.as_ref(),
+            allowlist.as_ref(),
+        )?;
+        let mint_record = self
+            .mint_record
+            .as_ref()
+            .ok_or(ErrorCode::MissingMintRecord)?;
+        MintRecord::track(
+            mint_record,
+            config,
+            owner.key,
+            allocation,
+            self.payer.as_ref(),
+            self.system_program.as_ref(),
+        )?;
 
+        let config_key = config.key();
+        let bump = [config.collection_authority_bump];
+        builder
+            .owner(Some(owner))
+            .authority(Some(collection_authority))
+            .invoke_signed(&[&CollectionConfig::collection_authority_seeds(
+                &config_key,
+                &bump,
+            )])?;
         Ok(())
     }
 }
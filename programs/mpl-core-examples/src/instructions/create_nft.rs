@@ -1,14 +1,19 @@
 pub use anchor_lang::prelude::*;
 use mpl_core::{
-    instructions::{CreateV1CpiBuilder, CreateV2CpiBuilder},
+    instructions::CreateV1CpiBuilder,
     types::{
-        ExternalCheckResult, ExternalPluginAdapterInitInfo, HookableLifecycleEvent, OracleInitInfo,
         PermanentBurnDelegate, PermanentFreezeDelegate, PermanentTransferDelegate, Plugin,
         PluginAuthority, PluginAuthorityPair,
     },
 };
 
-use crate::ONCHAIN_METAPLEX_ORACLE_PLUGIN;
+use crate::{
+    build_plugin_authority_pairs,
+    merkle::AllowlistProof,
+    state::{CollectionConfig, MintRecord},
+    validation::{validate_metadata, validate_program_collection, validate_program_mint},
+    PluginSpec, PluginTarget,
+};
 
 #[derive(Accounts)]
 pub struct CreateNFT<'info> {
@@ -35,6 +40,20 @@ pub struct CreateNFT<'info> {
     /// CHECK: Checked in mpl-core.
     pub owner: Option<AccountInfo<'info>>,
 
+    /// The config of a program-controlled collection, see `create_program_collection`.
+    /// When present, `authority` must be the config authority, or the owner must sign when minting
+    /// from the allowlist, and the collection authority PDA signs.
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+
+    /// The collection authority PDA of `collection_config`.
+    /// CHECK: Checked against the collection config.
+    pub collection_authority: Option<AccountInfo<'info>>,
+
+    /// The mint record of the owner in the collection of `collection_config`, created on its first mint.
+    /// CHECK: The address is checked and the account created in `MintRecord::track`.
+    #[account(mut)]
+    pub mint_record: Option<AccountInfo<'info>>,
+
     pub system_program: Program<'info, System>,
 
     /// CHECK: It is checked in the address constraint
@@ -48,6 +67,14 @@ pub struct CreateNFTArgs {
     pub uri: String,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateNFTWithPluginsArgs {
+    pub name: String,
+    pub uri: String,
+    /// The plugins to attach to the asset, each with the authority it should be created with.
+    pub plugins: Vec<PluginSpec>,
+}
+
 impl<'info> CreateNFT<'info> {
     /// For a standalone NFT, the collection should set to None(null in js)
     /// For an NFT as part of a collection, the collection should be passed but the
@@ -55,77 +82,132 @@ impl<'info> CreateNFT<'info> {
     /// This is because the update authority of the collection is automatically set as the
     /// update authority of the NFT when creating an NFT as part of a collection
     pub fn create_nft(&mut self, create_nft_args: CreateNFTArgs) -> Result<()> {
-        CreateV1CpiBuilder::new(&self.mpl_core_program)
-            .asset(self.asset.to_account_info().as_ref())
-            .collection(self.collection.as_ref())
-            .authority(self.authority.as_deref())
-            .owner(self.owner.as_ref())
-            .update_authority(self.update_authority.as_ref())
-            .payer(self.payer.to_account_info().as_ref())
-            .system_program(self.system_program.to_account_info().as_ref())
-            .name(create_nft_args.name)
-            .uri(create_nft_args.uri)
-            .invoke()?;
-        Ok(())
+        self.create(create_nft_args.name, create_nft_args.uri, vec![], None)
     }
 
     pub fn create_nft_with_permanent_freeze_delegate(&mut self, args: CreateNFTArgs) -> Result<()> {
-        CreateV1CpiBuilder::new(&self.mpl_core_program)
-            .payer(self.payer.to_account_info().as_ref())
-            .asset(self.asset.to_account_info().as_ref())
-            .owner(self.owner.as_ref())
-            .collection(self.collection.as_ref())
-            .name(args.name)
-            .system_program(self.system_program.to_account_info().as_ref())
-            .uri(args.uri)
-            .authority(self.authority.as_deref())
-            .update_authority(self.update_authority.as_ref())
-            .plugins(vec![PluginAuthorityPair {
+        self.create(
+            args.name,
+            args.uri,
+            vec![PluginAuthorityPair {
                 plugin: Plugin::PermanentFreezeDelegate(PermanentFreezeDelegate { frozen: true }),
                 authority: Some(PluginAuthority::UpdateAuthority),
-            }])
-            .invoke()?;
-        Ok(())
+            }],
+            None,
+        )
     }
 
     pub fn create_nft_with_permanent_transfer_delegate(
         &mut self,
         args: CreateNFTArgs,
     ) -> Result<()> {
-        CreateV1CpiBuilder::new(&self.mpl_core_program)
-            .payer(self.payer.to_account_info().as_ref())
-            .asset(self.asset.to_account_info().as_ref())
-            .owner(self.owner.as_ref())
-            .collection(self.collection.as_ref())
-            .name(args.name)
-            .system_program(self.system_program.to_account_info().as_ref())
-            .uri(args.uri)
-            .authority(self.authority.as_deref())
-            .update_authority(self.update_authority.as_ref())
-            .plugins(vec![PluginAuthorityPair {
+        self.create(
+            args.name,
+            args.uri,
+            vec![PluginAuthorityPair {
                 plugin: Plugin::PermanentTransferDelegate(PermanentTransferDelegate {}),
                 authority: Some(PluginAuthority::UpdateAuthority),
-            }])
-            .invoke()?;
-        Ok(())
+            }],
+            None,
+        )
     }
 
     pub fn create_nft_with_permanent_burn_delegate(&mut self, args: CreateNFTArgs) -> Result<()> {
-        CreateV1CpiBuilder::new(&self.mpl_core_program)
-            .payer(self.payer.to_account_info().as_ref())
-            .asset(self.asset.to_account_info().as_ref())
-            .owner(self.owner.as_ref())
+        self.create(
+            args.name,
+            args.uri,
+            vec![PluginAuthorityPair {
+                plugin: Plugin::PermanentBurnDelegate(PermanentBurnDelegate {}),
+                authority: Some(PluginAuthority::UpdateAuthority),
+            }],
+            None,
+        )
+    }
+
+    /// Creates an asset with any combination of plugins in a single CPI,
+    /// e.g. a permanent freeze delegate together with royalties.
+    /// Duplicate plugins and collection-only plugins are rejected before the CPI.
+    pub fn create_nft_with_plugins(&mut self, args: CreateNFTWithPluginsArgs) -> Result<()> {
+        let plugins = build_plugin_authority_pairs(args.plugins, PluginTarget::Asset)?;
+        self.create(args.name, args.uri, plugins, None)
+    }
+
+    /// Mints into a program-controlled collection with an allowlist.
+    /// The listed owner signs for itself, and can receive up to its allocation per phase.
+    pub fn create_nft_from_allowlist(
+        &mut self,
+        args: CreateNFTWithPluginsArgs,
+        proof: AllowlistProof,
+    ) -> Result<()> {
+        let plugins = build_plugin_authority_pairs(args.plugins, PluginTarget::Asset)?;
+        self.create(args.name, args.uri, plugins, Some(proof))
+    }
+
+    fn create(
+        &self,
+        name: String,
+        uri: String,
+        plugins: Vec<PluginAuthorityPair>,
+        allowlist: Option<AllowlistProof>,
+    ) -> Result<()> {
+        validate_metadata(&name, &uri)?;
+
+        let mut builder = CreateV1CpiBuilder::new(&self.mpl_core_program);
+        builder
+            .asset(self.asset.as_ref())
             .collection(self.collection.as_ref())
-            .name(args.name)
-            .system_program(self.system_program.to_account_info().as_ref())
-            .uri(args.uri)
             .authority(self.authority.as_deref())
+            .owner(self.owner.as_ref())
             .update_authority(self.update_authority.as_ref())
-            .plugins(vec![PluginAuthorityPair {
-                plugin: Plugin::PermanentBurnDelegate(PermanentBurnDelegate {}),
-                authority: Some(PluginAuthority::UpdateAuthority),
-            }])
-            .invoke()?;
+            .payer(self.payer.as_ref())
+            .system_program(self.system_program.as_ref())
+            .name(name)
+            .uri(uri);
+        if !plugins.is_empty() {
+            builder.plugins(plugins);
+        }
+
+        let Some(config) = &self.collection_config else {
+            require!(
+                allowlist.is_none(),
+                crate::error::ErrorCode::AllowlistNotSet
+            );
+            builder.invoke()?;
+            return Ok(());
+        };
+        let collection_authority = validate_program_collection(
+            config,
+            self.collection.as_ref(),
+            self.collection_authority.as_ref(),
+        )?;
+        let (owner, allocation) = validate_program_mint(
+            config,
+            self.authority.as_deref(),
+            self.owner.as_ref(),
+            allowlist.as_ref(),
+        )?;
+        let mint_record = self
+            .mint_record
+            .as_ref()
+            .ok_or(crate::error::ErrorCode::MissingMintRecord)?;
+        MintRecord::track(
+            mint_record,
+            config,
+            owner.key,
+            allocation,
+            self.payer.as_ref(),
+            self.system_program.as_ref(),
+        )?;
+
+        let config_key = config.key();
+        let bump = [config.collection_authority_bump];
+        builder
+            .owner(Some(owner))
+            .authority(Some(collection_authority))
+            .invoke_signed(&[&CollectionConfig::collection_authority_seeds(
+                &config_key,
+                &bump,
+            )])?;
         Ok(())
     }
 }
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use mpl_core::instructions::CreateCollectionV2CpiBuilder;
+
+use crate::{
+    build_external_plugin_adapters, build_plugin_authority_pairs, state::CollectionConfig,
+    validation::validate_metadata, CreateCollectionWithPluginsArgs, PluginTarget,
+    COLLECTION_AUTHORITY_SEED, COLLECTION_CONFIG_SEED, MPL_CORE_ID,
+};
+
+/// Creates a collection whose update authority is a PDA of this program instead of a wallet.
+/// Minting into it goes through this program, which signs for the PDA with `invoke_signed`,
+/// so mint rules can live on-chain instead of in a hot wallet.
+#[derive(Accounts)]
+pub struct CreateProgramCollection<'info> {
+    /// Account paying for rent and transaction fees.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The authority of the collection config, allowed to configure and mint from the collection.
+    pub authority: Signer<'info>,
+
+    /// The new collection account to be created.
+    #[account(mut)]
+    pub collection: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + CollectionConfig::INIT_SPACE,
+        seeds = [COLLECTION_CONFIG_SEED.as_bytes(), collection.key().as_ref()],
+        bump
+    )]
+    pub collection_config: Account<'info, CollectionConfig>,
+
+    /// The PDA set as update authority of the collection.
+    /// CHECK: Only used as an address, derived from the collection config.
+    #[account(
+        seeds = [COLLECTION_AUTHORITY_SEED.as_bytes(), collection_config.key().as_ref()],
+        bump
+    )]
+    pub collection_authority: AccountInfo<'info>,
+
+    /// Solana System Program (for account creation).
+    pub system_program: Program<'info, System>,
+
+    /// Metaplex Core program (CPI target).
+    /// CHECK: Address constraint ensures correctness.
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: AccountInfo<'info>,
+}
+
+impl<'info> CreateProgramCollection<'info> {
+    pub fn create_program_collection(
+        &mut self,
+        args: CreateCollectionWithPluginsArgs,
+        bumps: &CreateProgramCollectionBumps,
+    ) -> Result<()> {
+        validate_metadata(&args.name, &args.uri)?;
+        let plugins = build_plugin_authority_pairs(args.plugins, PluginTarget::Collection)?;
+        let external_plugin_adapters = build_external_plugin_adapters(
+            args.external_plugin_adapters,
+            PluginTarget::Collection,
+        )?;
+
+        self.collection_config.set_inner(CollectionConfig {
+            collection: self.collection.key(),
+            authority: self.authority.key(),
+            max_per_wallet: None,
+            phase: 0,
+            allowlist_root: None,
+            collection_authority_bump: bumps.collection_authority,
+            bump: bumps.collection_config,
+        });
+
+        let mut builder = CreateCollectionV2CpiBuilder::new(&self.mpl_core_program);
+        builder
+            .collection(self.collection.as_ref())
+            .update_authority(Some(&self.collection_authority))
+            .system_program(self.system_program.as_ref())
+            .payer(self.payer.as_ref())
+            .uri(args.uri)
+            .name(args.name);
+        if !plugins.is_empty() {
+            builder.plugins(plugins);
+        }
+        if !external_plugin_adapters.is_empty() {
+            builder.external_plugin_adapters(external_plugin_adapters);
+        }
+        builder.invoke()?;
+        Ok(())
+    }
+}
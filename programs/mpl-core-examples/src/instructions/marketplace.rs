@@ -0,0 +1,318 @@
+use anchor_lang::prelude::*;
+use mpl_core::{
+    fetch_asset_plugin,
+    instructions::{
+        AddPluginV1CpiBuilder, ApprovePluginAuthorityV1CpiBuilder,
+        RevokePluginAuthorityV1CpiBuilder, TransferV1CpiBuilder, UpdatePluginV1CpiBuilder,
+    },
+    types::{FreezeDelegate, Plugin, PluginAuthority, PluginType, TransferDelegate},
+};
+
+use crate::{
+    error::ErrorCode,
+    settlement::{settle_royalties, Payment},
+    state::Listing,
+    validation::validate_asset_collection,
+    LISTING_SEED, SPL_NOOP_PROGRAM,
+};
+
+/// Lists an asset for sale without escrow: the listing PDA becomes its transfer and
+/// freeze delegate and freezes it in the seller's wallet until it is bought or delisted.
+#[derive(Accounts)]
+pub struct List<'info> {
+    /// The owner of the asset.
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// The asset being listed.
+    /// CHECK: Checked in `validate_asset_collection` and mpl-core.
+    #[account(mut)]
+    pub asset: AccountInfo<'info>,
+
+    /// The collection of the asset, if it has one.
+    /// CHECK: Checked in `validate_asset_collection`.
+    #[account(mut)]
+    pub collection: Option<AccountInfo<'info>>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + Listing::INIT_SPACE,
+        seeds = [LISTING_SEED.as_bytes(), asset.key().as_ref()],
+        bump
+    )]
+    pub listing: Account<'info, Listing>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ListArgs {
+    /// In lamports, royalties included.
+    pub price: u64,
+    /// Unix timestamp after which the asset can't be bought anymore.
+    pub expiry: Option<i64>,
+}
+
+#[derive(Accounts)]
+pub struct Delist<'info> {
+    /// The seller, refunded the rent of the listing.
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// The listed asset.
+    /// CHECK: Checked against the listing and in mpl-core.
+    #[account(mut)]
+    pub asset: AccountInfo<'info>,
+
+    /// The collection of the asset, if it has one.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub collection: Option<AccountInfo<'info>>,
+
+    #[account(
+        mut,
+        close = seller,
+        seeds = [LISTING_SEED.as_bytes(), asset.key().as_ref()],
+        bump = listing.bump,
+        has_one = seller @ ErrorCode::UnauthorizedSigner,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+/// Buys a listed asset. The creators in the royalties of the asset (or of its collection)
+/// are passed as remaining accounts, in the order of the plugin.
+#[derive(Accounts)]
+pub struct Buy<'info> {
+    /// Pays the price and receives the asset.
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// Receives the price minus royalties and the rent of the listing.
+    /// CHECK: Checked against the listing.
+    #[account(mut)]
+    pub seller: AccountInfo<'info>,
+
+    /// The listed asset.
+    /// CHECK: Checked in `validate_asset_collection` and mpl-core.
+    #[account(mut)]
+    pub asset: AccountInfo<'info>,
+
+    /// The collection of the asset, if it has one.
+    /// CHECK: Checked in `validate_asset_collection`.
+    #[account(mut)]
+    pub collection: Option<AccountInfo<'info>>,
+
+    #[account(
+        mut,
+        close = seller,
+        seeds = [LISTING_SEED.as_bytes(), asset.key().as_ref()],
+        bump = listing.bump,
+        has_one = seller @ ErrorCode::SellerMismatch,
+    )]
+    pub listing: Account<'info, Listing>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+impl<'info> List<'info> {
+    /// Existing transfer and freeze delegates are handed to the listing, missing ones are added.
+    pub fn list(&mut self, args: ListArgs, bump: u8) -> Result<()> {
+        let asset = validate_asset_collection(&self.asset, self.collection.as_ref())?;
+        require_keys_eq!(
+            asset.owner,
+            self.seller.key(),
+            ErrorCode::UnauthorizedSigner
+        );
+        require!(args.price > 0, ErrorCode::InvalidListingPrice);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            !matches!(args.expiry, Some(expiry) if expiry <= now),
+            ErrorCode::InvalidListingExpiry
+        );
+
+        let asset_key = self.asset.key();
+        let listing_bump = [bump];
+
+        match fetch_asset_plugin::<TransferDelegate>(&self.asset, PluginType::TransferDelegate) {
+            Ok(_) => self.approve_listing(PluginType::TransferDelegate)?,
+            Err(_) => self.add_plugin(Plugin::TransferDelegate(TransferDelegate {}))?,
+        }
+
+        match fetch_asset_plugin::<FreezeDelegate>(&self.asset, PluginType::FreezeDelegate) {
+            Ok((_, freeze_delegate, _)) => {
+                require!(!freeze_delegate.frozen, ErrorCode::AssetFrozen);
+                self.approve_listing(PluginType::FreezeDelegate)?;
+
+                UpdatePluginV1CpiBuilder::new(&self.mpl_core)
+                    .asset(&self.asset)
+                    .collection(self.collection.as_ref())
+                    .payer(self.seller.as_ref())
+                    .authority(Some(self.listing.as_ref()))
+                    .system_program(self.system_program.as_ref())
+                    .log_wrapper(self.log_wrapper.as_ref())
+                    .plugin(Plugin::FreezeDelegate(FreezeDelegate { frozen: true }))
+                    .invoke_signed(&[&Listing::signer_seeds(&asset_key, &listing_bump)])?;
+            }
+            Err(_) => self.add_plugin(Plugin::FreezeDelegate(FreezeDelegate { frozen: true }))?,
+        }
+
+        self.listing.set_inner(Listing {
+            asset: asset_key,
+            seller: self.seller.key(),
+            price: args.price,
+            expiry: args.expiry,
+            bump,
+        });
+        Ok(())
+    }
+
+    fn approve_listing(&self, plugin_type: PluginType) -> Result<()> {
+        ApprovePluginAuthorityV1CpiBuilder::new(&self.mpl_core)
+            .asset(&self.asset)
+            .collection(self.collection.as_ref())
+            .payer(self.seller.as_ref())
+            .authority(Some(self.seller.as_ref()))
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin_type(plugin_type)
+            .new_authority(PluginAuthority::Address {
+                address: self.listing.key(),
+            })
+            .invoke()?;
+        Ok(())
+    }
+
+    fn add_plugin(&self, plugin: Plugin) -> Result<()> {
+        AddPluginV1CpiBuilder::new(&self.mpl_core)
+            .asset(&self.asset)
+            .collection(self.collection.as_ref())
+            .payer(self.seller.as_ref())
+            .authority(Some(self.seller.as_ref()))
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin(plugin)
+            .init_authority(PluginAuthority::Address {
+                address: self.listing.key(),
+            })
+            .invoke()?;
+        Ok(())
+    }
+}
+
+impl<'info> Delist<'info> {
+    /// Thaws the asset and gives both delegates back to the seller.
+    /// Expired listings are delisted the same way.
+    pub fn delist(&mut self) -> Result<()> {
+        let asset_key = self.asset.key();
+        let listing_bump = [self.listing.bump];
+
+        UpdatePluginV1CpiBuilder::new(&self.mpl_core)
+            .asset(&self.asset)
+            .collection(self.collection.as_ref())
+            .payer(self.seller.as_ref())
+            .authority(Some(self.listing.as_ref()))
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin(Plugin::FreezeDelegate(FreezeDelegate { frozen: false }))
+            .invoke_signed(&[&Listing::signer_seeds(&asset_key, &listing_bump)])?;
+
+        for plugin_type in [PluginType::FreezeDelegate, PluginType::TransferDelegate] {
+            RevokePluginAuthorityV1CpiBuilder::new(&self.mpl_core)
+                .asset(&self.asset)
+                .collection(self.collection.as_ref())
+                .payer(self.seller.as_ref())
+                .authority(Some(self.seller.as_ref()))
+                .system_program(self.system_program.as_ref())
+                .log_wrapper(self.log_wrapper.as_ref())
+                .plugin_type(plugin_type)
+                .invoke()?;
+        }
+        Ok(())
+    }
+}
+
+impl<'info> Buy<'info> {
+    /// Pays the creators their royalties and the seller the rest, then thaws the asset and
+    /// transfers it to the buyer with the listing PDA signing as transfer delegate.
+    /// mpl-core resets the owner-managed delegates on transfer, so the buyer gets a clean asset.
+    pub fn buy(&mut self, creators: &[AccountInfo<'info>]) -> Result<()> {
+        let asset = validate_asset_collection(&self.asset, self.collection.as_ref())?;
+        require_keys_eq!(asset.owner, self.seller.key(), ErrorCode::SellerMismatch);
+        require!(
+            !self.listing.is_expired(Clock::get()?.unix_timestamp),
+            ErrorCode::ListingExpired
+        );
+
+        let payment = Payment::Lamports {
+            payer: self.buyer.as_ref(),
+            system_program: self.system_program.as_ref(),
+        };
+        let price = self.listing.price;
+        let royalties = settle_royalties(
+            &self.asset,
+            self.collection.as_ref(),
+            price,
+            &payment,
+            creators,
+        )?;
+        payment.pay(&self.seller, price - royalties)?;
+
+        let asset_key = self.asset.key();
+        let listing_bump = [self.listing.bump];
+        let signer_seeds = Listing::signer_seeds(&asset_key, &listing_bump);
+
+        UpdatePluginV1CpiBuilder::new(&self.mpl_core)
+            .asset(&self.asset)
+            .collection(self.collection.as_ref())
+            .payer(self.buyer.as_ref())
+            .authority(Some(self.listing.as_ref()))
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin(Plugin::FreezeDelegate(FreezeDelegate { frozen: false }))
+            .invoke_signed(&[&signer_seeds])?;
+
+        TransferV1CpiBuilder::new(&self.mpl_core)
+            .asset(&self.asset)
+            .collection(self.collection.as_ref())
+            .payer(self.buyer.as_ref())
+            .authority(Some(self.listing.as_ref()))
+            .new_owner(self.buyer.as_ref())
+            .system_program(Some(self.system_program.as_ref()))
+            .log_wrapper(self.log_wrapper.as_ref())
+            .invoke_signed(&[&signer_seeds])?;
+        Ok(())
+    }
+}
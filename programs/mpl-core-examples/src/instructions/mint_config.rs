@@ -0,0 +1,374 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use mpl_core::instructions::CreateV1CpiBuilder;
+
+use crate::{
+    error::ErrorCode,
+    merkle::AllowlistProof,
+    state::{
+        render_template, CollectionConfig, KeyAssetRecord, MintConfig, MintPhase, MintRecord,
+        MintRule,
+    },
+    validation::{
+        validate_allowlist, validate_allowlist_proof, validate_holder, validate_metadata,
+        validate_program_collection,
+    },
+    MAX_NAME_LENGTH, MAX_URI_LENGTH, MINT_CONFIG_SEED, MINT_RECORD_SEED,
+};
+
+/// Opens a public mint for a program-controlled collection.
+/// Anyone can then buy an asset with `mint_from_config` while the rules allow it.
+#[derive(Accounts)]
+pub struct InitializeMintConfig<'info> {
+    /// Account paying for rent and transaction fees.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The authority of the collection config.
+    pub authority: Signer<'info>,
+
+    #[account(has_one = authority @ ErrorCode::UnauthorizedSigner)]
+    pub collection_config: Account<'info, CollectionConfig>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MintConfig::INIT_SPACE,
+        seeds = [MINT_CONFIG_SEED.as_bytes(), collection_config.collection.as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MintConfigArgs {
+    pub treasury: Pubkey,
+    pub max_supply: u32,
+    pub name_template: String,
+    pub uri_template: String,
+    pub phases: Vec<MintPhase>,
+}
+
+/// Changes the phases of a mint schedule that have not started yet.
+#[derive(Accounts)]
+pub struct UpdateMintPhases<'info> {
+    /// The authority of the collection config.
+    pub authority: Signer<'info>,
+
+    #[account(has_one = authority @ ErrorCode::UnauthorizedSigner)]
+    pub collection_config: Account<'info, CollectionConfig>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED.as_bytes(), collection_config.collection.as_ref()],
+        bump = mint_config.bump,
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateMintPhasesArgs {
+    /// The whole new schedule. Phases that have started must be passed unchanged.
+    pub phases: Vec<MintPhase>,
+}
+
+/// Changes the treasury or the metadata templates of a mint config.
+/// Assets already minted keep the name and URI they were minted with.
+#[derive(Accounts)]
+pub struct UpdateMintConfig<'info> {
+    /// The authority of the collection config.
+    pub authority: Signer<'info>,
+
+    #[account(has_one = authority @ ErrorCode::UnauthorizedSigner)]
+    pub collection_config: Account<'info, CollectionConfig>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED.as_bytes(), collection_config.collection.as_ref()],
+        bump = mint_config.bump,
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateMintConfigArgs {
+    pub treasury: Option<Pubkey>,
+    pub name_template: Option<String>,
+    pub uri_template: Option<String>,
+}
+
+#[derive(Accounts)]
+pub struct MintFromConfig<'info> {
+    /// Pays the price of the asset and receives it.
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// The new asset account.
+    #[account(mut)]
+    pub asset: Signer<'info>,
+
+    /// The program-controlled collection being minted from.
+    /// CHECK: Checked against the collection config.
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+
+    #[account(mut, has_one = collection @ ErrorCode::CollectionMismatch)]
+    pub collection_config: Account<'info, CollectionConfig>,
+
+    /// The collection authority PDA of `collection_config`, which signs the mint.
+    /// CHECK: Checked against the collection config.
+    pub collection_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED.as_bytes(), collection.key().as_ref()],
+        bump = mint_config.bump,
+        has_one = treasury @ ErrorCode::InvalidTreasury,
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// CHECK: Checked against the mint config, only receives lamports.
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + MintRecord::INIT_SPACE,
+        seeds = [MINT_RECORD_SEED.as_bytes(), collection.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub mint_record: Account<'info, MintRecord>,
+
+    /// An asset of the buyer from the collection of a holders phase.
+    /// CHECK: Deserialized and checked in `validate_holder`.
+    pub key_asset: Option<AccountInfo<'info>>,
+
+    /// Marks `key_asset` as used in a single use holders phase.
+    /// CHECK: The address is checked and the account created in `KeyAssetRecord::mark_used`.
+    #[account(mut)]
+    pub key_asset_record: Option<AccountInfo<'info>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: It is checked in the address constraint
+    #[account(address = mpl_core::ID)]
+    pub mpl_core_program: AccountInfo<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MintFromConfigArgs {
+    /// Required in allowlist phases.
+    pub allowlist: Option<AllowlistProof>,
+    /// Required while the collection config has an allowlist, which applies to every phase.
+    pub collection_allowlist: Option<AllowlistProof>,
+}
+
+impl<'info> InitializeMintConfig<'info> {
+    pub fn initialize_mint_config(&mut self, args: MintConfigArgs, bump: u8) -> Result<()> {
+        MintConfig::validate_phases(&args.phases)?;
+        require!(args.max_supply > 0, ErrorCode::InvalidMintSupply);
+        validate_templates(&args.name_template, &args.uri_template, args.max_supply)?;
+
+        self.mint_config.set_inner(MintConfig {
+            collection: self.collection_config.collection,
+            treasury: args.treasury,
+            max_supply: args.max_supply,
+            minted: 0,
+            name_template: args.name_template,
+            uri_template: args.uri_template,
+            phases: args.phases,
+            current_phase: None,
+            bump,
+        });
+        Ok(())
+    }
+}
+
+impl<'info> UpdateMintPhases<'info> {
+    /// Replaces the schedule. Started phases keep their index, so they can't be edited,
+    /// removed or have a phase inserted before them.
+    pub fn update_mint_phases(&mut self, args: UpdateMintPhasesArgs) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let current = &self.mint_config.phases;
+
+        for (index, phase) in current.iter().enumerate() {
+            if phase.has_started(now) {
+                require!(
+                    args.phases.get(index) == Some(phase),
+                    ErrorCode::MintPhaseStarted
+                );
+            }
+        }
+        for (index, phase) in args.phases.iter().enumerate() {
+            if current.get(index) != Some(phase) {
+                require!(!phase.has_started(now), ErrorCode::MintPhaseStarted);
+            }
+        }
+        MintConfig::validate_phases(&args.phases)?;
+
+        self.mint_config.phases = args.phases;
+        Ok(())
+    }
+}
+
+impl<'info> UpdateMintConfig<'info> {
+    pub fn update_mint_config(&mut self, args: UpdateMintConfigArgs) -> Result<()> {
+        let mint_config = &mut self.mint_config;
+        let name_template = args
+            .name_template
+            .unwrap_or_else(|| mint_config.name_template.clone());
+        let uri_template = args
+            .uri_template
+            .unwrap_or_else(|| mint_config.uri_template.clone());
+        validate_templates(&name_template, &uri_template, mint_config.max_supply)?;
+
+        if let Some(treasury) = args.treasury {
+            mint_config.treasury = treasury;
+        }
+        mint_config.name_template = name_template;
+        mint_config.uri_template = uri_template;
+        Ok(())
+    }
+}
+
+impl<'info> MintFromConfig<'info> {
+    /// Charges the buyer the price of the active phase and mints them the next asset
+    /// of the collection, signed by the collection authority PDA.
+    pub fn mint_from_config(
+        &mut self,
+        args: MintFromConfigArgs,
+        bumps: &MintFromConfigBumps,
+    ) -> Result<()> {
+        require!(
+            self.mint_config.minted < self.mint_config.max_supply,
+            ErrorCode::MintSoldOut
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let (index, phase) = self.mint_config.active_phase(now)?;
+        let phase = phase.clone();
+
+        let collection_allocation = validate_allowlist(
+            &self.collection_config,
+            self.buyer.key,
+            args.collection_allowlist.as_ref(),
+        )?;
+        let allocation = match &phase.rule {
+            MintRule::Public => None,
+            MintRule::Allowlist { root } => Some(validate_allowlist_proof(
+                root,
+                self.buyer.key,
+                args.allowlist.as_ref(),
+            )?),
+            MintRule::Holders {
+                collection,
+                single_use,
+            } => {
+                let key_asset = self.key_asset.as_ref().ok_or(ErrorCode::NotAHolder)?;
+                validate_holder(key_asset, self.buyer.key, collection)?;
+                if *single_use {
+                    let key_asset_record = self
+                        .key_asset_record
+                        .as_ref()
+                        .ok_or(ErrorCode::InvalidKeyAssetRecord)?;
+                    KeyAssetRecord::mark_used(
+                        key_asset_record,
+                        &self.collection_config,
+                        key_asset.key,
+                        self.buyer.as_ref(),
+                        self.system_program.as_ref(),
+                    )?;
+                }
+                None
+            }
+        };
+
+        // Entering a new phase of the schedule resets the mint count of every wallet
+        if self.mint_config.current_phase != Some(index) {
+            self.mint_config.current_phase = Some(index);
+            self.collection_config.phase = self
+                .collection_config
+                .phase
+                .checked_add(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+
+        if self.mint_record.minter == Pubkey::default() {
+            self.mint_record.set_inner(MintRecord {
+                collection: self.collection.key(),
+                minter: self.buyer.key(),
+                minted: 0,
+                phase: self.collection_config.phase,
+                bump: bumps.mint_record,
+            });
+        }
+        // The strictest of the phase, collection-wide and allowlist limits applies
+        let max_per_wallet = phase
+            .max_per_wallet
+            .into_iter()
+            .chain(self.collection_config.max_per_wallet)
+            .chain(allocation)
+            .chain(collection_allocation)
+            .min();
+        self.mint_record
+            .record_mint(self.collection_config.phase, max_per_wallet)?;
+
+        if phase.price > 0 {
+            transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.buyer.to_account_info(),
+                        to: self.treasury.to_account_info(),
+                    },
+                ),
+                phase.price,
+            )?;
+        }
+
+        self.mint_config.minted += 1;
+        let (name, uri) = self.mint_config.asset_metadata(self.mint_config.minted);
+
+        let collection_authority = validate_program_collection(
+            &self.collection_config,
+            Some(&self.collection),
+            Some(&self.collection_authority),
+        )?;
+        let config_key = self.collection_config.key();
+        let bump = [self.collection_config.collection_authority_bump];
+
+        CreateV1CpiBuilder::new(&self.mpl_core_program)
+            .asset(self.asset.as_ref())
+            .collection(Some(&self.collection))
+            .authority(Some(collection_authority))
+            .owner(Some(self.buyer.as_ref()))
+            .payer(self.buyer.as_ref())
+            .system_program(self.system_program.as_ref())
+            .name(name)
+            .uri(uri)
+            .invoke_signed(&[&CollectionConfig::collection_authority_seeds(
+                &config_key,
+                &bump,
+            )])?;
+        Ok(())
+    }
+}
+
+/// The templates are stored in the mint config, so they must fit in its `#[max_len]`,
+/// and so must the metadata they render to.
+fn validate_templates(name_template: &str, uri_template: &str, max_supply: u32) -> Result<()> {
+    require!(
+        name_template.len() <= MAX_NAME_LENGTH,
+        ErrorCode::NameTooLong
+    );
+    require!(uri_template.len() <= MAX_URI_LENGTH, ErrorCode::UriTooLong);
+    // The last mint number renders the longest metadata
+    validate_metadata(
+        &render_template(name_template, max_supply),
+        &render_template(uri_template, max_supply),
+    )
+}
@@ -1,19 +1,55 @@
 pub mod add_plugins_to_collections;
 pub mod add_plugins_to_nft;
+pub mod burn_collection;
 pub mod burn_nft;
+pub mod collection_config;
+pub mod collection_plugin_authority;
 pub mod create_collection;
 pub mod create_edition;
 pub mod create_nft;
+pub mod create_program_collection;
+pub mod marketplace;
+pub mod mint_config;
+pub mod move_nft;
+pub mod nft_plugin_authority;
+pub mod offers;
+pub mod program_delegate;
+pub mod remove_autograph;
+pub mod remove_collection_plugin;
+pub mod remove_nft_plugin;
+pub mod settle_royalties;
+pub mod staking;
 pub mod transfer_nft;
+pub mod update_authority_handover;
+pub mod update_collection;
+pub mod update_collection_plugin;
 pub mod update_nft_metadata;
 pub mod update_nft_plugin;
 
 pub use add_plugins_to_collections::*;
 pub use add_plugins_to_nft::*;
+pub use burn_collection::*;
 pub use burn_nft::*;
+pub use collection_config::*;
+pub use collection_plugin_authority::*;
 pub use create_collection::*;
 pub use create_edition::*;
 pub use create_nft::*;
+pub use create_program_collection::*;
+pub use marketplace::*;
+pub use mint_config::*;
+pub use move_nft::*;
+pub use nft_plugin_authority::*;
+pub use offers::*;
+pub use program_delegate::*;
+pub use remove_autograph::*;
+pub use remove_collection_plugin::*;
+pub use remove_nft_plugin::*;
+pub use settle_royalties::*;
+pub use staking::*;
 pub use transfer_nft::*;
+pub use update_authority_handover::*;
+pub use update_collection::*;
+pub use update_collection_plugin::*;
 pub use update_nft_metadata::*;
 pub use update_nft_plugin::*;
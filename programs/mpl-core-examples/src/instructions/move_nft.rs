@@ -0,0 +1,132 @@
+use anchor_lang::prelude::*;
+use mpl_core::{instructions::UpdateV2CpiBuilder, types::UpdateAuthority};
+
+use crate::{
+    error::ErrorCode,
+    state::CollectionConfig,
+    validation::{
+        invoke_as_collection_authority, validate_asset_collection, validate_asset_update_authority,
+        validate_collection_update_authority,
+    },
+    SPL_NOOP_PROGRAM,
+};
+
+/// Moves an asset into, out of, or between collections with `UpdateV2`.
+/// `UpdateV1` (see `UpdateNFTMetadata`) can't change the collection of an asset.
+///
+/// mpl-core takes a single authority for the whole move, so the signer must be the update
+/// authority (or an update delegate) of the asset or its current collection, and also of the
+/// new collection when there is one. For a program-controlled collection, pass its config
+/// and the collection authority PDA signs in place of `authority`.
+#[derive(Accounts)]
+pub struct MoveNFT<'info> {
+    /// The account paying for the storage fees.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The asset being moved.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub asset: AccountInfo<'info>,
+
+    /// The collection the asset is part of today, if any.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub collection: Option<AccountInfo<'info>>,
+
+    /// The collection the asset is moved into, if any.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub new_collection: Option<AccountInfo<'info>>,
+
+    /// The update authority of the old and new collections.
+    pub authority: Signer<'info>,
+
+    /// The config of a program-controlled collection, see `create_program_collection`.
+    /// When present, `authority` must be the config authority and the collection authority PDA signs.
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+
+    /// The collection authority PDA of `collection_config`.
+    /// CHECK: Checked against the collection config.
+    pub collection_authority: Option<AccountInfo<'info>>,
+
+    /// The system program.
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MoveNFTArgs {
+    /// The update authority of the asset once it leaves its collection.
+    /// Defaults to the signing authority. Ignored when moving into a collection.
+    pub new_update_authority: Option<Pubkey>,
+}
+
+impl<'info> MoveNFT<'info> {
+    pub fn move_nft(&mut self, args: MoveNFTArgs) -> Result<()> {
+        let asset = validate_asset_collection(&self.asset, self.collection.as_ref())?;
+        let signer = match (&self.collection_config, &self.collection_authority) {
+            (Some(_), Some(collection_authority)) => collection_authority.key(),
+            _ => self.authority.key(),
+        };
+
+        match &self.collection {
+            Some(collection) => {
+                validate_collection_update_authority(collection, &signer)?;
+            }
+            None => validate_asset_update_authority(&self.asset, &asset, &signer)?,
+        }
+
+        let new_update_authority = match (&self.collection, &self.new_collection) {
+            (_, Some(new_collection)) => {
+                if let Some(collection) = &self.collection {
+                    require_keys_neq!(
+                        *collection.key,
+                        *new_collection.key,
+                        ErrorCode::SameCollection
+                    );
+                }
+                validate_collection_update_authority(new_collection, &signer)?;
+                UpdateAuthority::Collection(*new_collection.key)
+            }
+            (Some(_), None) => {
+                UpdateAuthority::Address(args.new_update_authority.unwrap_or(self.authority.key()))
+            }
+            (None, None) => return err!(ErrorCode::NoCollectionChange),
+        };
+
+        // The program-controlled collection can be either side of the move
+        let program_collection = self.collection_config.as_ref().and_then(|config| {
+            [self.collection.as_ref(), self.new_collection.as_ref()]
+                .into_iter()
+                .flatten()
+                .find(|collection| *collection.key == config.collection)
+        });
+
+        let mut builder = UpdateV2CpiBuilder::new(&self.mpl_core);
+        builder
+            .asset(&self.asset)
+            .collection(self.collection.as_ref())
+            .new_collection(self.new_collection.as_ref())
+            .payer(self.payer.as_ref())
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .new_update_authority(new_update_authority);
+        invoke_as_collection_authority(
+            self.collection_config.as_ref(),
+            program_collection,
+            Some(self.authority.as_ref()),
+            self.collection_authority.as_ref(),
+            |authority, signer_seeds| builder.authority(authority).invoke_signed(signer_seeds),
+        )
+    }
+}
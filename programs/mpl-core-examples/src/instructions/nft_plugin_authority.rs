@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use mpl_core::{
+    fetch_asset_plugin,
+    instructions::{ApprovePluginAuthorityV1CpiBuilder, RevokePluginAuthorityV1CpiBuilder},
+    types::{PluginAuthority, PluginType},
+};
+
+use crate::{
+    error::ErrorCode, validation::validate_asset_collection, PluginAuthorityArgs, PluginTypeArgs,
+    SPL_NOOP_PROGRAM,
+};
+
+/// The owner-managed delegates cleared by `revoke_all_owner_managed_delegates`.
+const OWNER_MANAGED_DELEGATES: [PluginType; 3] = [
+    PluginType::FreezeDelegate,
+    PluginType::TransferDelegate,
+    PluginType::BurnDelegate,
+];
+
+/// Hands the authority of an existing plugin to someone else after minting,
+/// e.g. a freeze delegate to a staking program or a transfer delegate to a marketplace.
+#[derive(Accounts)]
+pub struct NFTPluginAuthority<'info> {
+    /// The address of the asset.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub asset: AccountInfo<'info>,
+
+    /// The collection to which the asset might belong to.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub collection: Option<AccountInfo<'info>>,
+
+    /// The account paying for the storage fees.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The current authority of the plugin, usually the owner.
+    pub authority: Option<Signer<'info>>,
+
+    /// The system program.
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ApprovePluginAuthorityArgs {
+    pub plugin_type: PluginTypeArgs,
+    pub new_authority: PluginAuthorityArgs,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RevokePluginAuthorityArgs {
+    pub plugin_type: PluginTypeArgs,
+}
+
+impl<'info> NFTPluginAuthority<'info> {
+    /// Sets `new_authority` as the authority of a plugin already on the asset.
+    pub fn approve_plugin_authority(&mut self, args: ApprovePluginAuthorityArgs) -> Result<()> {
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
+        // The owner must always be able to take an owner-managed plugin back.
+        if args.plugin_type.is_owner_managed() {
+            require!(
+                args.new_authority != PluginAuthorityArgs::None,
+                ErrorCode::InvalidPluginAuthority
+            );
+        }
+
+        let plugin_type = PluginType::from(args.plugin_type);
+        fetch_asset_plugin::<()>(self.asset.as_ref(), plugin_type.clone())
+            .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        ApprovePluginAuthorityV1CpiBuilder::new(&self.mpl_core)
+            .asset(self.asset.as_ref())
+            .collection(self.collection.as_ref())
+            .payer(self.payer.as_ref())
+            .authority(self.authority.as_deref())
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin_type(plugin_type)
+            .new_authority(args.new_authority.into())
+            .invoke()?;
+        Ok(())
+    }
+
+    /// Gives the authority of a plugin back to its default, the owner or the update authority.
+    pub fn revoke_plugin_authority(&mut self, args: RevokePluginAuthorityArgs) -> Result<()> {
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
+        let plugin_type = PluginType::from(args.plugin_type);
+        fetch_asset_plugin::<()>(self.asset.as_ref(), plugin_type.clone())
+            .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        self.revoke(plugin_type)
+    }
+
+    /// Revokes every freeze, transfer and burn delegate on the asset in one go,
+    /// e.g. before selling it. Plugins that are missing or already held by the owner are skipped.
+    pub fn revoke_all_owner_managed_delegates(&mut self) -> Result<()> {
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
+        for plugin_type in OWNER_MANAGED_DELEGATES {
+            match fetch_asset_plugin::<()>(self.asset.as_ref(), plugin_type.clone()) {
+                Ok((PluginAuthority::Owner, _, _)) | Err(_) => continue,
+                Ok(_) => self.revoke(plugin_type)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn revoke(&self, plugin_type: PluginType) -> Result<()> {
+        RevokePluginAuthorityV1CpiBuilder::new(&self.mpl_core)
+            .asset(self.asset.as_ref())
+            .collection(self.collection.as_ref())
+            .payer(self.payer.as_ref())
+            .authority(self.authority.as_deref())
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin_type(plugin_type)
+            .invoke()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,262 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use mpl_core::{
+    fetch_asset_plugin,
+    instructions::{
+        AddPluginV1CpiBuilder, ApprovePluginAuthorityV1CpiBuilder, TransferV1CpiBuilder,
+    },
+    types::{Plugin, PluginAuthority, PluginType, TransferDelegate},
+};
+
+use crate::{
+    error::ErrorCode,
+    settlement::{settle_royalties, Payment},
+    state::Offer,
+    validation::{load_collection, validate_asset_collection},
+    OFFER_SEED, SPL_NOOP_PROGRAM,
+};
+
+/// Bids on any asset of a collection. The lamports for every asset wanted are escrowed
+/// in the offer PDA until the offer is filled, cancelled or expires.
+#[derive(Accounts)]
+pub struct MakeOffer<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// The collection the offer is on.
+    /// CHECK: Deserialized in `load_collection`.
+    pub collection: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + Offer::INIT_SPACE,
+        seeds = [OFFER_SEED.as_bytes(), collection.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct MakeOfferArgs {
+    /// In lamports per asset, royalties included.
+    pub price: u64,
+    /// How many assets the buyer wants.
+    pub quantity: u32,
+    /// Unix timestamp after which the offer can only be refunded.
+    pub expiry: Option<i64>,
+}
+
+/// Sells an asset into an offer. The creators in the royalties of the asset (or of its
+/// collection) are passed as remaining accounts, in the order of the plugin.
+#[derive(Accounts)]
+pub struct AcceptOffer<'info> {
+    /// The owner of the asset, paid the price minus royalties.
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// Receives the asset, and the rent of the offer once it is filled.
+    /// CHECK: Checked against the offer.
+    #[account(mut)]
+    pub buyer: AccountInfo<'info>,
+
+    /// The asset being sold.
+    /// CHECK: Checked in `validate_asset_collection` and mpl-core.
+    #[account(mut)]
+    pub asset: AccountInfo<'info>,
+
+    /// The collection of the asset.
+    /// CHECK: Checked against the offer and in `validate_asset_collection`.
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [OFFER_SEED.as_bytes(), collection.key().as_ref(), buyer.key().as_ref()],
+        bump = offer.bump,
+    )]
+    pub offer: Account<'info, Offer>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+/// Withdraws an offer, refunding the escrow and the rent to the buyer.
+#[derive(Accounts)]
+pub struct CancelOffer<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = buyer,
+        seeds = [OFFER_SEED.as_bytes(), offer.collection.as_ref(), buyer.key().as_ref()],
+        bump = offer.bump,
+    )]
+    pub offer: Account<'info, Offer>,
+}
+
+/// Closes an expired offer, refunding the escrow and the rent to the buyer.
+/// Anyone can send it, so buyers get their lamports back without coming back for them.
+#[derive(Accounts)]
+pub struct RefundExpiredOffer<'info> {
+    /// CHECK: Checked against the offer, only receives lamports.
+    #[account(mut)]
+    pub buyer: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        close = buyer,
+        seeds = [OFFER_SEED.as_bytes(), offer.collection.as_ref(), buyer.key().as_ref()],
+        bump = offer.bump,
+    )]
+    pub offer: Account<'info, Offer>,
+}
+
+impl<'info> MakeOffer<'info> {
+    pub fn make_offer(&mut self, args: MakeOfferArgs, bump: u8) -> Result<()> {
+        load_collection(&self.collection)?;
+        require!(args.price > 0, ErrorCode::InvalidOfferPrice);
+        require!(args.quantity > 0, ErrorCode::InvalidOfferQuantity);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            !matches!(args.expiry, Some(expiry) if expiry <= now),
+            ErrorCode::InvalidOfferExpiry
+        );
+        let escrow = args
+            .price
+            .checked_mul(args.quantity as u64)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        self.offer.set_inner(Offer {
+            collection: self.collection.key(),
+            buyer: self.buyer.key(),
+            price: args.price,
+            quantity: args.quantity,
+            expiry: args.expiry,
+            bump,
+        });
+
+        transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.buyer.to_account_info(),
+                    to: self.offer.to_account_info(),
+                },
+            ),
+            escrow,
+        )
+    }
+}
+
+impl<'info> AcceptOffer<'info> {
+    /// Moves the asset to the buyer with the offer PDA signing as transfer delegate, then pays
+    /// the creators and the seller from the escrow. Each sale fills one asset of the offer,
+    /// which is closed to the buyer once the last one is filled.
+    pub fn accept_offer(&mut self, creators: &[AccountInfo<'info>]) -> Result<()> {
+        let asset = validate_asset_collection(&self.asset, Some(&self.collection))?;
+        require_keys_eq!(
+            asset.owner,
+            self.seller.key(),
+            ErrorCode::UnauthorizedSigner
+        );
+        require!(
+            !self.offer.is_expired(Clock::get()?.unix_timestamp),
+            ErrorCode::OfferExpired
+        );
+
+        let delegate = PluginAuthority::Address {
+            address: self.offer.key(),
+        };
+        match fetch_asset_plugin::<TransferDelegate>(&self.asset, PluginType::TransferDelegate) {
+            Ok(_) => {
+                ApprovePluginAuthorityV1CpiBuilder::new(&self.mpl_core)
+                    .asset(&self.asset)
+                    .collection(Some(&self.collection))
+                    .payer(self.seller.as_ref())
+                    .authority(Some(self.seller.as_ref()))
+                    .system_program(self.system_program.as_ref())
+                    .log_wrapper(self.log_wrapper.as_ref())
+                    .plugin_type(PluginType::TransferDelegate)
+                    .new_authority(delegate)
+                    .invoke()?;
+            }
+            Err(_) => {
+                AddPluginV1CpiBuilder::new(&self.mpl_core)
+                    .asset(&self.asset)
+                    .collection(Some(&self.collection))
+                    .payer(self.seller.as_ref())
+                    .authority(Some(self.seller.as_ref()))
+                    .system_program(self.system_program.as_ref())
+                    .log_wrapper(self.log_wrapper.as_ref())
+                    .plugin(Plugin::TransferDelegate(TransferDelegate {}))
+                    .init_authority(delegate)
+                    .invoke()?;
+            }
+        }
+
+        let collection = self.collection.key();
+        let buyer = self.buyer.key();
+        let bump = [self.offer.bump];
+        TransferV1CpiBuilder::new(&self.mpl_core)
+            .asset(&self.asset)
+            .collection(Some(&self.collection))
+            .payer(self.seller.as_ref())
+            .authority(Some(self.offer.as_ref()))
+            .new_owner(&self.buyer)
+            .system_program(Some(self.system_program.as_ref()))
+            .log_wrapper(self.log_wrapper.as_ref())
+            .invoke_signed(&[&Offer::signer_seeds(&collection, &buyer, &bump)])?;
+
+        // Lamports are moved out of the escrow directly, after the CPIs
+        let payment = Payment::Escrow {
+            account: self.offer.as_ref(),
+        };
+        let price = self.offer.price;
+        let royalties = settle_royalties(
+            &self.asset,
+            Some(&self.collection),
+            price,
+            &payment,
+            creators,
+        )?;
+        payment.pay(self.seller.as_ref(), price - royalties)?;
+
+        self.offer.quantity -= 1;
+        if self.offer.quantity == 0 {
+            self.offer.close(self.buyer.clone())?;
+        }
+        Ok(())
+    }
+}
+
+impl<'info> CancelOffer<'info> {
+    /// The `close` constraint refunds the escrow along with the rent.
+    pub fn cancel_offer(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'info> RefundExpiredOffer<'info> {
+    pub fn refund_expired_offer(&mut self) -> Result<()> {
+        require!(
+            self.offer.is_expired(Clock::get()?.unix_timestamp),
+            ErrorCode::OfferNotExpired
+        );
+        Ok(())
+    }
+}
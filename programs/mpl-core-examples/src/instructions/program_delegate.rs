@@ -0,0 +1,203 @@
+use anchor_lang::prelude::*;
+use mpl_core::{
+    fetch_asset_plugin, fetch_collection_plugin,
+    instructions::{
+        RevokeCollectionPluginAuthorityV1CpiBuilder, RevokePluginAuthorityV1CpiBuilder,
+        UpdatePluginV1CpiBuilder,
+    },
+    types::{FreezeDelegate, Plugin, PluginAuthority, PluginType},
+};
+
+use crate::{
+    error::ErrorCode,
+    validation::{
+        validate_asset_collection, validate_asset_update_authority,
+        validate_collection_update_authority,
+    },
+    RevokePluginAuthorityArgs, DELEGATE_SEED, SPL_NOOP_PROGRAM,
+};
+
+/// Signs for plugins held by this program's delegate PDA (see [`crate::program_delegate`]),
+/// so the owner can always thaw the asset or take the plugin back.
+#[derive(Accounts)]
+pub struct ProgramDelegate<'info> {
+    /// The address of the asset.
+    /// CHECK: Checked in `validate_asset_collection` and mpl-core.
+    #[account(mut)]
+    pub asset: AccountInfo<'info>,
+
+    /// The collection to which the asset might belong to.
+    /// CHECK: Checked in `validate_asset_collection`.
+    #[account(mut)]
+    pub collection: Option<AccountInfo<'info>>,
+
+    /// The account paying for the storage fees.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The owner of the asset, or its update authority for plugins that aren't owner-managed.
+    pub authority: Signer<'info>,
+
+    /// This program's delegate PDA, signing as the plugin authority.
+    /// CHECK: Derived from the seeds, only signs.
+    #[account(seeds = [DELEGATE_SEED.as_bytes()], bump)]
+    pub program_delegate: AccountInfo<'info>,
+
+    /// The system program.
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+/// Same as [`ProgramDelegate`] for the plugins of a collection, like its royalties.
+#[derive(Accounts)]
+pub struct CollectionProgramDelegate<'info> {
+    /// The address of the collection.
+    /// CHECK: Checked in `validate_collection_update_authority` and mpl-core.
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+
+    /// The account paying for the storage fees.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The update authority of the collection or one of its update delegates.
+    pub authority: Signer<'info>,
+
+    /// This program's delegate PDA, signing as the plugin authority.
+    /// CHECK: Derived from the seeds, only signs.
+    #[account(seeds = [DELEGATE_SEED.as_bytes()], bump)]
+    pub program_delegate: AccountInfo<'info>,
+
+    /// The system program.
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+impl<'info> ProgramDelegate<'info> {
+    /// Thaws an asset frozen by a freeze delegate held by the program PDA.
+    /// Only the owner can ask for it.
+    pub fn thaw(&mut self, bump: u8) -> Result<()> {
+        let asset = validate_asset_collection(&self.asset, self.collection.as_ref())?;
+        require_keys_eq!(
+            asset.owner,
+            self.authority.key(),
+            ErrorCode::UnauthorizedSigner
+        );
+        self.require_program_authority(PluginType::FreezeDelegate)?;
+
+        self.set_frozen(false, bump)
+    }
+
+    /// Gives a plugin held by the program PDA back to its default authority: the owner for
+    /// owner-managed plugins, the update authority otherwise. A frozen asset is thawed first,
+    /// since mpl-core won't revoke a freeze delegate while it is frozen.
+    pub fn revoke(&mut self, args: RevokePluginAuthorityArgs, bump: u8) -> Result<()> {
+        let asset = validate_asset_collection(&self.asset, self.collection.as_ref())?;
+        if args.plugin_type.is_owner_managed() {
+            require_keys_eq!(
+                asset.owner,
+                self.authority.key(),
+                ErrorCode::UnauthorizedSigner
+            );
+        } else if let Some(collection) = &self.collection {
+            validate_collection_update_authority(collection, &self.authority.key())?;
+        } else {
+            validate_asset_update_authority(&self.asset, &asset, &self.authority.key())?;
+        }
+
+        let plugin_type = PluginType::from(args.plugin_type);
+        self.require_program_authority(plugin_type.clone())?;
+
+        if plugin_type == PluginType::FreezeDelegate {
+            let (_, freeze_delegate, _) =
+                fetch_asset_plugin::<FreezeDelegate>(&self.asset, PluginType::FreezeDelegate)?;
+            if freeze_delegate.frozen {
+                self.set_frozen(false, bump)?;
+            }
+        }
+
+        RevokePluginAuthorityV1CpiBuilder::new(&self.mpl_core)
+            .asset(&self.asset)
+            .collection(self.collection.as_ref())
+            .payer(self.payer.as_ref())
+            .authority(Some(&self.program_delegate))
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin_type(plugin_type)
+            .invoke_signed(&[&[DELEGATE_SEED.as_bytes(), &[bump]]])?;
+        Ok(())
+    }
+
+    fn require_program_authority(&self, plugin_type: PluginType) -> Result<()> {
+        let (authority, _, _) = fetch_asset_plugin::<()>(&self.asset, plugin_type)
+            .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+        require!(
+            authority
+                == PluginAuthority::Address {
+                    address: self.program_delegate.key()
+                },
+            ErrorCode::InvalidPluginAuthority
+        );
+        Ok(())
+    }
+
+    fn set_frozen(&self, frozen: bool, bump: u8) -> Result<()> {
+        UpdatePluginV1CpiBuilder::new(&self.mpl_core)
+            .asset(&self.asset)
+            .collection(self.collection.as_ref())
+            .payer(self.payer.as_ref())
+            .authority(Some(&self.program_delegate))
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin(Plugin::FreezeDelegate(FreezeDelegate { frozen }))
+            .invoke_signed(&[&[DELEGATE_SEED.as_bytes(), &[bump]]])?;
+        Ok(())
+    }
+}
+
+impl<'info> CollectionProgramDelegate<'info> {
+    /// Gives a collection plugin held by the program PDA back to the update authority.
+    pub fn revoke(&mut self, args: RevokePluginAuthorityArgs, bump: u8) -> Result<()> {
+        validate_collection_update_authority(&self.collection, &self.authority.key())?;
+
+        let plugin_type = PluginType::from(args.plugin_type);
+        let (authority, _, _) =
+            fetch_collection_plugin::<()>(&self.collection, plugin_type.clone())
+                .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+        require!(
+            authority
+                == PluginAuthority::Address {
+                    address: self.program_delegate.key()
+                },
+            ErrorCode::InvalidPluginAuthority
+        );
+
+        RevokeCollectionPluginAuthorityV1CpiBuilder::new(&self.mpl_core)
+            .collection(&self.collection)
+            .payer(self.payer.as_ref())
+            .authority(Some(&self.program_delegate))
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin_type(plugin_type)
+            .invoke_signed(&[&[DELEGATE_SEED.as_bytes(), &[bump]]])?;
+        Ok(())
+    }
+}
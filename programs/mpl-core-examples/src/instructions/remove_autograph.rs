@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use mpl_core::{
+    fetch_asset_plugin,
+    instructions::UpdatePluginV1CpiBuilder,
+    types::{Autograph, Plugin, PluginType},
+};
+
+use crate::{
+    error::ErrorCode,
+    validation::{validate_asset_collection, validate_plugin_authority},
+    SPL_NOOP_PROGRAM,
+};
+
+/// mpl-core lets anyone append their own autograph, but only the plugin authority (the owner)
+/// can take signatures away. Removing an autograph therefore needs the autographer to sign
+/// for their own signature and the owner to approve the update.
+#[derive(Accounts)]
+pub struct RemoveAutograph<'info> {
+    /// The address of the asset.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub asset: AccountInfo<'info>,
+
+    /// The collection to which the asset might belong to.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub collection: Option<AccountInfo<'info>>,
+
+    /// The account paying for the storage fees.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The address whose autograph is removed.
+    pub autographer: Signer<'info>,
+
+    /// The authority of the autograph plugin. Can be left out when the autographer is the owner.
+    pub authority: Option<Signer<'info>>,
+
+    /// The system program.
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+impl<'info> RemoveAutograph<'info> {
+    /// Removes the autograph of the signing autographer and keeps every other signature.
+    pub fn remove_own_autograph(&mut self) -> Result<()> {
+        let asset = validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
+        let (plugin_authority, mut autograph, _) =
+            fetch_asset_plugin::<Autograph>(self.asset.as_ref(), PluginType::Autograph)
+                .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        let authority = self.authority.as_ref().unwrap_or(&self.autographer);
+        validate_plugin_authority(&plugin_authority, &asset, authority.key)?;
+
+        let position = autograph
+            .signatures
+            .iter()
+            .position(|signature| signature.address == self.autographer.key())
+            .ok_or(ErrorCode::AutographNotFound)?;
+        autograph.signatures.remove(position);
+
+        UpdatePluginV1CpiBuilder::new(&self.mpl_core)
+            .payer(self.payer.as_ref())
+            .asset(self.asset.as_ref())
+            .collection(self.collection.as_ref())
+            .authority(Some(authority.as_ref()))
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin(Plugin::Autograph(autograph))
+            .invoke()?;
+        Ok(())
+    }
+}
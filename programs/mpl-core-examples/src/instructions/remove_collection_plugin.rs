@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+use mpl_core::{
+    accounts::BaseCollectionV1,
+    fetch_collection_plugin, fetch_wrapped_external_plugin_adapter,
+    instructions::{
+        RemoveCollectionExternalPluginAdapterV1CpiBuilder, RemoveCollectionPluginV1CpiBuilder,
+    },
+    types::{ExternalPluginAdapterKey, PluginType},
+};
+
+use crate::{
+    error::ErrorCode,
+    state::CollectionConfig,
+    validation::{invoke_as_collection_authority, load_collection},
+    RemoveExternalPluginAdapterArgs, RemovePluginArgs, SPL_NOOP_PROGRAM,
+};
+
+#[derive(Accounts)]
+pub struct RemoveCollectionPlugin<'info> {
+    /// The address of the collection.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+
+    /// The account receiving the rent freed by the removed plugin.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The update authority of the collection or the authority of the plugin.
+    pub authority: Option<Signer<'info>>,
+
+    /// The config of a program-controlled collection, see `create_program_collection`.
+    /// When present, `authority` must be the config authority and the collection authority PDA signs.
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+
+    /// The collection authority PDA of `collection_config`.
+    /// CHECK: Checked against the collection config.
+    pub collection_authority: Option<AccountInfo<'info>>,
+
+    /// The system program.
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+impl<'info> RemoveCollectionPlugin<'info> {
+    /// Removes a plugin from a collection. Its assets stop inheriting it right away.
+    pub fn remove_plugin(&mut self, args: RemovePluginArgs) -> Result<()> {
+        load_collection(&self.collection)?;
+
+        let plugin_type = PluginType::from(args.plugin_type);
+        // Only presence is checked, but mpl-core still deserializes the whole plugin
+        fetch_collection_plugin::<()>(self.collection.as_ref(), plugin_type.clone())
+            .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        let mut builder = RemoveCollectionPluginV1CpiBuilder::new(&self.mpl_core);
+        builder
+            .collection(self.collection.as_ref())
+            .payer(self.payer.as_ref())
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin_type(plugin_type);
+        invoke_as_collection_authority(
+            self.collection_config.as_ref(),
+            Some(&self.collection),
+            self.authority.as_deref(),
+            self.collection_authority.as_ref(),
+            |authority, signer_seeds| builder.authority(authority).invoke_signed(signer_seeds),
+        )
+    }
+
+    /// Removes an external plugin adapter (e.g. an oracle) from a collection.
+    pub fn remove_external_plugin_adapter(
+        &mut self,
+        args: RemoveExternalPluginAdapterArgs,
+    ) -> Result<()> {
+        load_collection(&self.collection)?;
+
+        let key = ExternalPluginAdapterKey::from(args.key);
+        fetch_wrapped_external_plugin_adapter::<BaseCollectionV1>(
+            self.collection.as_ref(),
+            None,
+            &key,
+        )
+        .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        let mut builder = RemoveCollectionExternalPluginAdapterV1CpiBuilder::new(&self.mpl_core);
+        builder
+            .collection(self.collection.as_ref())
+            .payer(self.payer.as_ref())
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .key(key);
+        invoke_as_collection_authority(
+            self.collection_config.as_ref(),
+            Some(&self.collection),
+            self.authority.as_deref(),
+            self.collection_authority.as_ref(),
+            |authority, signer_seeds| builder.authority(authority).invoke_signed(signer_seeds),
+        )
+    }
+}
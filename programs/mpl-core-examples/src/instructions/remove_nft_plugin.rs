@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use mpl_core::{
+    accounts::BaseAssetV1,
+    fetch_asset_plugin, fetch_wrapped_external_plugin_adapter,
+    instructions::{RemoveExternalPluginAdapterV1CpiBuilder, RemovePluginV1CpiBuilder},
+    types::{ExternalPluginAdapterKey, PluginType},
+};
+
+use crate::{
+    error::ErrorCode, validation::validate_asset_collection, ExternalPluginAdapterKeyArgs,
+    PluginTypeArgs, SPL_NOOP_PROGRAM,
+};
+
+#[derive(Accounts)]
+pub struct RemoveNFTPlugin<'info> {
+    /// The address of the asset.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub asset: AccountInfo<'info>,
+
+    /// The collection to which the asset might belong to.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub collection: Option<AccountInfo<'info>>,
+
+    /// The account receiving the rent freed by the removed plugin.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The authority of the plugin.
+    pub authority: Option<Signer<'info>>,
+
+    /// The system program.
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RemovePluginArgs {
+    pub plugin_type: PluginTypeArgs,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RemoveExternalPluginAdapterArgs {
+    pub key: ExternalPluginAdapterKeyArgs,
+}
+
+impl<'info> RemoveNFTPlugin<'info> {
+    /// Removes a plugin from an asset.
+    /// Owner-managed plugins are removed by the owner, authority-managed ones by the update authority.
+    pub fn remove_plugin(&mut self, args: RemovePluginArgs) -> Result<()> {
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
+        let plugin_type = PluginType::from(args.plugin_type);
+        // Only presence is checked, but mpl-core still deserializes the whole plugin
+        fetch_asset_plugin::<()>(self.asset.as_ref(), plugin_type.clone())
+            .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        RemovePluginV1CpiBuilder::new(&self.mpl_core)
+            .asset(self.asset.as_ref())
+            .collection(self.collection.as_ref())
+            .payer(self.payer.as_ref())
+            .authority(self.authority.as_deref())
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin_type(plugin_type)
+            .invoke()?;
+        Ok(())
+    }
+
+    /// Removes an external plugin adapter (e.g. an oracle) from an asset.
+    pub fn remove_external_plugin_adapter(
+        &mut self,
+        args: RemoveExternalPluginAdapterArgs,
+    ) -> Result<()> {
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
+        let key = ExternalPluginAdapterKey::from(args.key);
+        fetch_wrapped_external_plugin_adapter::<BaseAssetV1>(self.asset.as_ref(), None, &key)
+            .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        RemoveExternalPluginAdapterV1CpiBuilder::new(&self.mpl_core)
+            .asset(self.asset.as_ref())
+            .collection(self.collection.as_ref())
+            .payer(self.payer.as_ref())
+            .authority(self.authority.as_deref())
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .key(key)
+            .invoke()?;
+        Ok(())
+    }
+}
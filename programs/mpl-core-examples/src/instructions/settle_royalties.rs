@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    error::ErrorCode,
+    settlement::{settle_royalties, Payment},
+    validation::validate_asset_collection,
+};
+
+/// Pays the royalties of a sale made outside this program, in lamports or in an SPL token.
+/// The creators in the royalties of the asset (or of its collection) are passed as remaining
+/// accounts, in the order of the plugin: their wallets for lamports, their token accounts
+/// for tokens.
+#[derive(Accounts)]
+pub struct SettleRoyalties<'info> {
+    /// Pays the royalties.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The asset that was sold.
+    /// CHECK: Checked in `validate_asset_collection`.
+    pub asset: AccountInfo<'info>,
+
+    /// The collection of the asset, if it has one.
+    /// CHECK: Checked in `validate_asset_collection`.
+    pub collection: Option<AccountInfo<'info>>,
+
+    /// The token the sale was paid in. Lamports when not set.
+    #[account(mint::token_program = token_program)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, token::authority = payer)]
+    pub payer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SettleRoyaltiesArgs {
+    /// The sale price, in lamports or base units of the mint.
+    pub price: u64,
+}
+
+impl<'info> SettleRoyalties<'info> {
+    pub fn settle_royalties(
+        &mut self,
+        args: SettleRoyaltiesArgs,
+        creators: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
+        let payment = match (&self.mint, &self.payer_token_account, &self.token_program) {
+            (None, None, None) => Payment::Lamports {
+                payer: self.payer.as_ref(),
+                system_program: self.system_program.as_ref(),
+            },
+            (Some(mint), Some(from), Some(token_program)) => {
+                require_keys_eq!(from.mint, mint.key(), ErrorCode::PaymentAccountsRequired);
+                Payment::Tokens {
+                    authority: self.payer.as_ref(),
+                    from,
+                    mint,
+                    token_program,
+                }
+            }
+            _ => return err!(ErrorCode::PaymentAccountsRequired),
+        };
+
+        settle_royalties(
+            &self.asset,
+            self.collection.as_ref(),
+            args.price,
+            &payment,
+            creators,
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,656 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+use mpl_core::{
+    fetch_asset_plugin,
+    instructions::{
+        AddPluginV1CpiBuilder, ApprovePluginAuthorityV1CpiBuilder,
+        RevokePluginAuthorityV1CpiBuilder, UpdatePluginV1CpiBuilder,
+    },
+    types::{Attributes, FreezeDelegate, Plugin, PluginAuthority, PluginType},
+};
+
+use crate::{
+    error::ErrorCode,
+    state::{PendingRewards, RewardMultiplier, StakePool, StakeRecord},
+    validation::{validate_asset_collection, validate_collection_update_authority},
+    MAX_ATTRIBUTE_LENGTH, MAX_REWARD_MULTIPLIERS, PENDING_REWARDS_SEED, REWARD_VAULT_SEED,
+    SPL_NOOP_PROGRAM, STAKE_POOL_SEED, STAKE_RECORD_SEED,
+};
+
+/// Opens escrowless staking for the assets of a collection.
+#[derive(Accounts)]
+pub struct InitializeStakePool<'info> {
+    /// The update authority (or an update delegate) of the collection, who configures the pool.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The collection whose assets can be staked.
+    /// CHECK: Checked in `validate_collection_update_authority`.
+    pub collection: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + StakePool::INIT_SPACE,
+        seeds = [STAKE_POOL_SEED.as_bytes(), collection.key().as_ref()],
+        bump
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InitializeStakePoolArgs {
+    /// Rewards earned per staked asset and second.
+    pub reward_rate: u64,
+}
+
+/// Creates the vault paying the rewards of a pool in an SPL or Token-2022 token.
+/// Rewards already earned are paid from it too.
+#[derive(Accounts)]
+pub struct InitializeRewardVault<'info> {
+    /// The authority of the pool.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::UnauthorizedSigner,
+        constraint = stake_pool.reward_mint.is_none() @ ErrorCode::InvalidRewardAccount,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    /// Token account owned by the pool PDA.
+    #[account(
+        init,
+        payer = authority,
+        seeds = [REWARD_VAULT_SEED.as_bytes(), stake_pool.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = stake_pool,
+        token::token_program = token_program,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Tops up the reward vault of a pool. Anyone can fund it.
+#[derive(Accounts)]
+pub struct FundRewardVault<'info> {
+    pub funder: Signer<'info>,
+
+    #[account(
+        constraint = stake_pool.reward_mint == Some(reward_mint.key()) @ ErrorCode::InvalidRewardAccount,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [REWARD_VAULT_SEED.as_bytes(), stake_pool.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = funder,
+        token::token_program = token_program,
+    )]
+    pub funder_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct FundRewardVaultArgs {
+    pub amount: u64,
+}
+
+/// Changes the reward settings of a pool.
+#[derive(Accounts)]
+pub struct UpdateStakePool<'info> {
+    /// The authority of the pool.
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = authority @ ErrorCode::UnauthorizedSigner)]
+    pub stake_pool: Account<'info, StakePool>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetRewardRateArgs {
+    /// Rewards earned per staked asset and second.
+    pub reward_rate: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetRewardMultipliersArgs {
+    /// Key of the `Attributes` entry to read from staked assets, `None` to stop using multipliers.
+    pub attribute: Option<String>,
+    pub multipliers: Vec<RewardMultiplier>,
+}
+
+/// Staking keeps the asset in the owner's wallet: the pool PDA becomes its freeze delegate
+/// and freezes it until it is unstaked.
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    /// The owner of the asset.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The asset being staked.
+    /// CHECK: Checked in `validate_asset_collection` and mpl-core.
+    #[account(mut)]
+    pub asset: AccountInfo<'info>,
+
+    /// The collection of the asset.
+    /// CHECK: Checked against the stake pool and the asset.
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [STAKE_POOL_SEED.as_bytes(), collection.key().as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + StakeRecord::INIT_SPACE,
+        seeds = [STAKE_RECORD_SEED.as_bytes(), asset.key().as_ref()],
+        bump
+    )]
+    pub stake_record: Account<'info, StakeRecord>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    /// The owner of the asset, refunded the rent of the stake record.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The staked asset.
+    /// CHECK: Checked against the stake record and in mpl-core.
+    #[account(mut)]
+    pub asset: AccountInfo<'info>,
+
+    /// The collection of the asset.
+    /// CHECK: Checked against the stake pool and in mpl-core.
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [STAKE_POOL_SEED.as_bytes(), collection.key().as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [STAKE_RECORD_SEED.as_bytes(), asset.key().as_ref()],
+        bump = stake_record.bump,
+        has_one = owner @ ErrorCode::UnauthorizedSigner,
+        constraint = stake_record.pool == stake_pool.key() @ ErrorCode::CollectionMismatch,
+    )]
+    pub stake_record: Account<'info, StakeRecord>,
+
+    /// Keeps the rewards the reward vault can't pay once the stake record is closed.
+    /// Only required when some are left unpaid, it is then created if needed.
+    /// CHECK: The address is checked and the account created in `PendingRewards::add`.
+    #[account(mut)]
+    pub pending_rewards: Option<AccountInfo<'info>>,
+
+    /// The reward token of the pool, required once it has a reward vault.
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [REWARD_VAULT_SEED.as_bytes(), stake_pool.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the rewards.
+    #[account(mut)]
+    pub owner_reward_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimStakingRewards<'info> {
+    /// The owner of the staked asset.
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [STAKE_POOL_SEED.as_bytes(), stake_pool.collection.as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(
+        mut,
+        seeds = [STAKE_RECORD_SEED.as_bytes(), stake_record.asset.as_ref()],
+        bump = stake_record.bump,
+        has_one = owner @ ErrorCode::UnauthorizedSigner,
+        constraint = stake_record.pool == stake_pool.key() @ ErrorCode::CollectionMismatch,
+    )]
+    pub stake_record: Account<'info, StakeRecord>,
+
+    /// The reward token of the pool, required once it has a reward vault.
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [REWARD_VAULT_SEED.as_bytes(), stake_pool.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the rewards.
+    #[account(mut)]
+    pub owner_reward_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+/// Pays the rewards an owner was still owed when unstaking.
+#[derive(Accounts)]
+pub struct ClaimPendingRewards<'info> {
+    /// The owner the rewards are owed to, refunded the rent of the pending rewards once paid.
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [STAKE_POOL_SEED.as_bytes(), stake_pool.collection.as_ref()],
+        bump = stake_pool.bump,
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(
+        mut,
+        seeds = [PENDING_REWARDS_SEED.as_bytes(), stake_pool.key().as_ref(), owner.key().as_ref()],
+        bump = pending_rewards.bump,
+        has_one = owner @ ErrorCode::UnauthorizedSigner,
+    )]
+    pub pending_rewards: Account<'info, PendingRewards>,
+
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [REWARD_VAULT_SEED.as_bytes(), stake_pool.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Receives the rewards.
+    #[account(mut)]
+    pub owner_reward_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> InitializeStakePool<'info> {
+    pub fn initialize_stake_pool(&mut self, args: InitializeStakePoolArgs, bump: u8) -> Result<()> {
+        validate_collection_update_authority(&self.collection, self.authority.key)?;
+
+        self.stake_pool.set_inner(StakePool {
+            collection: self.collection.key(),
+            authority: self.authority.key(),
+            reward_rate: args.reward_rate,
+            reward_per_asset: 0,
+            last_update: Clock::get()?.unix_timestamp,
+            total_staked: 0,
+            total_rewards_claimed: 0,
+            reward_mint: None,
+            multiplier_attribute: None,
+            multipliers: vec![],
+            bump,
+        });
+        Ok(())
+    }
+}
+
+impl<'info> InitializeRewardVault<'info> {
+    pub fn initialize_reward_vault(&mut self) -> Result<()> {
+        self.stake_pool.reward_mint = Some(self.reward_mint.key());
+        Ok(())
+    }
+}
+
+impl<'info> FundRewardVault<'info> {
+    pub fn fund_reward_vault(&mut self, args: FundRewardVaultArgs) -> Result<()> {
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.funder_token_account.to_account_info(),
+                    mint: self.reward_mint.to_account_info(),
+                    to: self.reward_vault.to_account_info(),
+                    authority: self.funder.to_account_info(),
+                },
+            ),
+            args.amount,
+            self.reward_mint.decimals,
+        )
+    }
+}
+
+impl<'info> UpdateStakePool<'info> {
+    /// Rewards up to now are checkpointed at the old rate before the new one applies.
+    pub fn set_reward_rate(&mut self, args: SetRewardRateArgs) -> Result<()> {
+        self.stake_pool.accrue(Clock::get()?.unix_timestamp)?;
+        self.stake_pool.reward_rate = args.reward_rate;
+        Ok(())
+    }
+
+    /// Multipliers are read when an asset is staked, so assets already staked keep theirs.
+    pub fn set_reward_multipliers(&mut self, args: SetRewardMultipliersArgs) -> Result<()> {
+        require!(
+            args.multipliers.len() <= MAX_REWARD_MULTIPLIERS,
+            ErrorCode::TooManyRewardMultipliers
+        );
+        let too_long = args
+            .attribute
+            .iter()
+            .chain(args.multipliers.iter().map(|multiplier| &multiplier.value))
+            .any(|text| text.len() > MAX_ATTRIBUTE_LENGTH);
+        require!(!too_long, ErrorCode::RewardAttributeTooLong);
+
+        self.stake_pool.multiplier_attribute = args.attribute;
+        self.stake_pool.multipliers = args.multipliers;
+        Ok(())
+    }
+}
+
+impl<'info> Stake<'info> {
+    /// Freezes the asset under the pool PDA and starts accruing its rewards.
+    /// An existing freeze delegate is handed to the pool, otherwise one is added.
+    pub fn stake(&mut self, bump: u8) -> Result<()> {
+        let asset = validate_asset_collection(&self.asset, Some(&self.collection))?;
+        require_keys_eq!(asset.owner, self.owner.key(), ErrorCode::UnauthorizedSigner);
+
+        let pool = self.stake_pool.key();
+        let collection = self.collection.key();
+        let pool_bump = [self.stake_pool.bump];
+
+        match fetch_asset_plugin::<FreezeDelegate>(&self.asset, PluginType::FreezeDelegate) {
+            Ok((_, freeze_delegate, _)) => {
+                require!(!freeze_delegate.frozen, ErrorCode::AssetFrozen);
+
+                ApprovePluginAuthorityV1CpiBuilder::new(&self.mpl_core)
+                    .asset(&self.asset)
+                    .collection(Some(&self.collection))
+                    .payer(self.owner.as_ref())
+                    .authority(Some(self.owner.as_ref()))
+                    .system_program(self.system_program.as_ref())
+                    .log_wrapper(self.log_wrapper.as_ref())
+                    .plugin_type(PluginType::FreezeDelegate)
+                    .new_authority(PluginAuthority::Address { address: pool })
+                    .invoke()?;
+
+                UpdatePluginV1CpiBuilder::new(&self.mpl_core)
+                    .asset(&self.asset)
+                    .collection(Some(&self.collection))
+                    .payer(self.owner.as_ref())
+                    .authority(Some(self.stake_pool.as_ref()))
+                    .system_program(self.system_program.as_ref())
+                    .log_wrapper(self.log_wrapper.as_ref())
+                    .plugin(Plugin::FreezeDelegate(FreezeDelegate { frozen: true }))
+                    .invoke_signed(&[&StakePool::signer_seeds(&collection, &pool_bump)])?;
+            }
+            Err(_) => {
+                AddPluginV1CpiBuilder::new(&self.mpl_core)
+                    .asset(&self.asset)
+                    .collection(Some(&self.collection))
+                    .payer(self.owner.as_ref())
+                    .authority(Some(self.owner.as_ref()))
+                    .system_program(self.system_program.as_ref())
+                    .log_wrapper(self.log_wrapper.as_ref())
+                    .plugin(Plugin::FreezeDelegate(FreezeDelegate { frozen: true }))
+                    .init_authority(PluginAuthority::Address { address: pool })
+                    .invoke()?;
+            }
+        }
+
+        let multiplier_bps = self.reward_multiplier();
+        let now = Clock::get()?.unix_timestamp;
+        self.stake_pool.accrue(now)?;
+        self.stake_pool.total_staked += 1;
+        self.stake_record.set_inner(StakeRecord {
+            pool,
+            asset: self.asset.key(),
+            owner: self.owner.key(),
+            start_time: now,
+            reward_debt: self.stake_pool.reward_per_asset,
+            multiplier_bps,
+            unpaid_rewards: 0,
+            rewards_claimed: 0,
+            bump,
+        });
+        Ok(())
+    }
+
+    /// Multiplier selected by the value of the pool's multiplier attribute on the asset.
+    fn reward_multiplier(&self) -> u16 {
+        let value = self
+            .stake_pool
+            .multiplier_attribute
+            .as_ref()
+            .and_then(|key| {
+                let (_, attributes, _) =
+                    fetch_asset_plugin::<Attributes>(&self.asset, PluginType::Attributes).ok()?;
+                attributes
+                    .attribute_list
+                    .into_iter()
+                    .find(|attribute| &attribute.key == key)
+                    .map(|attribute| attribute.value)
+            });
+        self.stake_pool.multiplier_for(value.as_deref())
+    }
+}
+
+impl<'info> Unstake<'info> {
+    /// Claims the pending rewards, thaws the asset and gives the freeze delegate back to the owner.
+    /// Rewards the vault can't cover move to the pending rewards of the owner,
+    /// so unstaking never depends on its balance and nothing owed is lost.
+    pub fn unstake(&mut self) -> Result<()> {
+        self.stake_pool.accrue(Clock::get()?.unix_timestamp)?;
+        pay_rewards(
+            &mut self.stake_pool,
+            &mut self.stake_record,
+            self.reward_mint.as_ref(),
+            self.reward_vault.as_ref(),
+            self.owner_reward_account.as_ref(),
+            self.token_program.as_ref(),
+        )?;
+        self.stake_pool.total_staked -= 1;
+
+        let unpaid = self.stake_record.unpaid_rewards;
+        if unpaid > 0 {
+            let pending_rewards = self
+                .pending_rewards
+                .as_ref()
+                .ok_or(ErrorCode::MissingPendingRewards)?;
+            PendingRewards::add(
+                pending_rewards,
+                &self.stake_pool.key(),
+                self.owner.key,
+                unpaid,
+                self.owner.as_ref(),
+                self.system_program.as_ref(),
+            )?;
+        }
+
+        let collection = self.collection.key();
+        let pool_bump = [self.stake_pool.bump];
+
+        UpdatePluginV1CpiBuilder::new(&self.mpl_core)
+            .asset(&self.asset)
+            .collection(Some(&self.collection))
+            .payer(self.owner.as_ref())
+            .authority(Some(self.stake_pool.as_ref()))
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin(Plugin::FreezeDelegate(FreezeDelegate { frozen: false }))
+            .invoke_signed(&[&StakePool::signer_seeds(&collection, &pool_bump)])?;
+
+        RevokePluginAuthorityV1CpiBuilder::new(&self.mpl_core)
+            .asset(&self.asset)
+            .collection(Some(&self.collection))
+            .payer(self.owner.as_ref())
+            .authority(Some(self.owner.as_ref()))
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin_type(PluginType::FreezeDelegate)
+            .invoke()?;
+        Ok(())
+    }
+}
+
+impl<'info> ClaimStakingRewards<'info> {
+    /// Pays the rewards accrued since the last claim without unstaking.
+    /// Whatever the vault can't cover stays owed to the asset for a later claim.
+    pub fn claim_staking_rewards(&mut self) -> Result<()> {
+        self.stake_pool.accrue(Clock::get()?.unix_timestamp)?;
+        pay_rewards(
+            &mut self.stake_pool,
+            &mut self.stake_record,
+            self.reward_mint.as_ref(),
+            self.reward_vault.as_ref(),
+            self.owner_reward_account.as_ref(),
+            self.token_program.as_ref(),
+        )
+    }
+}
+
+impl<'info> ClaimPendingRewards<'info> {
+    /// Pays as much of the pending rewards as the reward vault holds, the rest stays pending.
+    /// The account is closed once everything is paid.
+    pub fn claim_pending_rewards(&mut self) -> Result<()> {
+        let paid = pay_from_vault(
+            &self.stake_pool,
+            self.pending_rewards.amount,
+            Some(&self.reward_mint),
+            Some(&self.reward_vault),
+            Some(&self.owner_reward_account),
+            Some(&self.token_program),
+        )?;
+        self.pending_rewards.amount -= paid;
+        self.stake_pool.record_claim(paid)?;
+        if self.pending_rewards.amount == 0 {
+            self.pending_rewards.close(self.owner.to_account_info())?;
+        }
+        Ok(())
+    }
+}
+
+/// Settles the rewards of `stake_record` and pays as much of them as the reward vault holds.
+/// The rest stays in `unpaid_rewards` for a later claim.
+fn pay_rewards<'info>(
+    stake_pool: &mut Account<'info, StakePool>,
+    stake_record: &mut StakeRecord,
+    reward_mint: Option<&InterfaceAccount<'info, Mint>>,
+    reward_vault: Option<&InterfaceAccount<'info, TokenAccount>>,
+    destination: Option<&InterfaceAccount<'info, TokenAccount>>,
+    token_program: Option<&Interface<'info, TokenInterface>>,
+) -> Result<()> {
+    let owed = stake_pool.settle(stake_record)?;
+    let paid = pay_from_vault(
+        stake_pool,
+        owed,
+        reward_mint,
+        reward_vault,
+        destination,
+        token_program,
+    )?;
+    stake_pool.record_payout(stake_record, paid)
+}
+
+/// Transfers up to `owed` rewards from the reward vault to `destination` and returns the amount paid.
+/// Pools without a reward vault pay nothing, their rewards stay owed until one is created.
+fn pay_from_vault<'info>(
+    stake_pool: &Account<'info, StakePool>,
+    owed: u64,
+    reward_mint: Option<&InterfaceAccount<'info, Mint>>,
+    reward_vault: Option<&InterfaceAccount<'info, TokenAccount>>,
+    destination: Option<&InterfaceAccount<'info, TokenAccount>>,
+    token_program: Option<&Interface<'info, TokenInterface>>,
+) -> Result<u64> {
+    let Some(mint_key) = stake_pool.reward_mint else {
+        return Ok(0);
+    };
+    let (Some(reward_mint), Some(reward_vault), Some(destination), Some(token_program)) =
+        (reward_mint, reward_vault, destination, token_program)
+    else {
+        return err!(ErrorCode::RewardAccountsRequired);
+    };
+    require_keys_eq!(reward_mint.key(), mint_key, ErrorCode::InvalidRewardAccount);
+    require_keys_eq!(destination.mint, mint_key, ErrorCode::InvalidRewardAccount);
+
+    let amount = owed.min(reward_vault.amount);
+    if amount > 0 {
+        let collection = stake_pool.collection;
+        let bump = [stake_pool.bump];
+        transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                TransferChecked {
+                    from: reward_vault.to_account_info(),
+                    mint: reward_mint.to_account_info(),
+                    to: destination.to_account_info(),
+                    authority: stake_pool.to_account_info(),
+                },
+                &[&StakePool::signer_seeds(&collection, &bump)],
+            ),
+            amount,
+            reward_mint.decimals,
+        )?;
+    }
+    Ok(amount)
+}
@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use mpl_core::instructions::TransferV1CpiBuilder;
 
-use crate::SPL_NOOP_PROGRAM;
+use crate::{validation::validate_asset_collection, SPL_NOOP_PROGRAM};
 
 #[derive(Accounts)]
 pub struct TransferNFT<'info> {
@@ -39,6 +39,8 @@ pub struct TransferNFT<'info> {
 
 impl<'info> TransferNFT<'info> {
     pub fn handler(&mut self) -> Result<()> {
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
         TransferV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
             .asset(self.asset.to_account_info().as_ref())
             .collection(self.collection.as_ref())
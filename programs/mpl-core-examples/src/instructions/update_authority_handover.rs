@@ -0,0 +1,189 @@
+use anchor_lang::prelude::*;
+use mpl_core::instructions::UpdateCollectionV1CpiBuilder;
+
+use crate::{
+    error::ErrorCode, state::UpdateAuthorityProposal, validation::load_collection,
+    UPDATE_AUTHORITY_PROPOSAL_SEED,
+};
+
+/// Two-step handover of a collection's update authority.
+///
+/// Proposing moves the update authority to the proposal PDA. From there it can only go to
+/// the proposed authority (`accept`) or back to the proposer (`cancel`), so a typo in the
+/// new address can't orphan the collection.
+#[derive(Accounts)]
+pub struct ProposeUpdateAuthority<'info> {
+    /// The current update authority of the collection.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The collection being handed over.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + UpdateAuthorityProposal::INIT_SPACE,
+        seeds = [UPDATE_AUTHORITY_PROPOSAL_SEED.as_bytes(), collection.key().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, UpdateAuthorityProposal>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: It is checked in the address constraint
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptUpdateAuthority<'info> {
+    /// The proposed update authority.
+    #[account(mut)]
+    pub new_authority: Signer<'info>,
+
+    /// The authority that made the proposal, refunded the rent of the proposal.
+    /// CHECK: Checked against the proposal.
+    #[account(mut)]
+    pub proposer: AccountInfo<'info>,
+
+    /// The collection being handed over.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [UPDATE_AUTHORITY_PROPOSAL_SEED.as_bytes(), collection.key().as_ref()],
+        bump = proposal.bump,
+        has_one = proposer @ ErrorCode::UnauthorizedSigner,
+        has_one = new_authority @ ErrorCode::UnauthorizedSigner,
+    )]
+    pub proposal: Account<'info, UpdateAuthorityProposal>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: It is checked in the address constraint
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelUpdateAuthority<'info> {
+    /// The authority that made the proposal, who gets the update authority back.
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    /// The collection being handed over.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [UPDATE_AUTHORITY_PROPOSAL_SEED.as_bytes(), collection.key().as_ref()],
+        bump = proposal.bump,
+        has_one = proposer @ ErrorCode::UnauthorizedSigner,
+    )]
+    pub proposal: Account<'info, UpdateAuthorityProposal>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: It is checked in the address constraint
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ProposeUpdateAuthorityArgs {
+    pub new_authority: Pubkey,
+}
+
+impl<'info> ProposeUpdateAuthority<'info> {
+    pub fn propose_update_authority(
+        &mut self,
+        args: ProposeUpdateAuthorityArgs,
+        bump: u8,
+    ) -> Result<()> {
+        let collection = load_collection(&self.collection)?;
+        require_keys_eq!(
+            collection.update_authority,
+            self.authority.key(),
+            ErrorCode::UnauthorizedSigner
+        );
+        require_keys_neq!(
+            args.new_authority,
+            self.authority.key(),
+            ErrorCode::SameUpdateAuthority
+        );
+
+        self.proposal.set_inner(UpdateAuthorityProposal {
+            collection: self.collection.key(),
+            proposer: self.authority.key(),
+            new_authority: args.new_authority,
+            bump,
+        });
+
+        // The PDA holds the update authority until the proposal is accepted or cancelled
+        UpdateCollectionV1CpiBuilder::new(&self.mpl_core)
+            .collection(&self.collection)
+            .payer(self.authority.as_ref())
+            .authority(Some(self.authority.as_ref()))
+            .new_update_authority(Some(self.proposal.as_ref()))
+            .system_program(self.system_program.as_ref())
+            .invoke()?;
+        Ok(())
+    }
+}
+
+impl<'info> AcceptUpdateAuthority<'info> {
+    pub fn accept_update_authority(&mut self) -> Result<()> {
+        release_update_authority(
+            &self.mpl_core,
+            &self.collection,
+            &self.proposal,
+            &self.new_authority,
+            &self.system_program,
+        )
+    }
+}
+
+impl<'info> CancelUpdateAuthority<'info> {
+    pub fn cancel_update_authority(&mut self) -> Result<()> {
+        release_update_authority(
+            &self.mpl_core,
+            &self.collection,
+            &self.proposal,
+            &self.proposer,
+            &self.system_program,
+        )
+    }
+}
+
+/// Hands the update authority held by the proposal PDA to `recipient`, who also pays the CPI.
+fn release_update_authority<'info>(
+    mpl_core: &AccountInfo<'info>,
+    collection: &AccountInfo<'info>,
+    proposal: &Account<'info, UpdateAuthorityProposal>,
+    recipient: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        UPDATE_AUTHORITY_PROPOSAL_SEED.as_bytes(),
+        collection.key.as_ref(),
+        &[proposal.bump],
+    ]];
+
+    UpdateCollectionV1CpiBuilder::new(mpl_core)
+        .collection(collection)
+        .payer(recipient)
+        .authority(Some(proposal.as_ref()))
+        .new_update_authority(Some(recipient))
+        .system_program(system_program)
+        .invoke_signed(signer_seeds)?;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use mpl_core::instructions::UpdateCollectionV1CpiBuilder;
+
+use crate::{
+    error::ErrorCode,
+    state::CollectionConfig,
+    validation::{invoke_as_collection_authority, load_collection, validate_optional_metadata},
+    SPL_NOOP_PROGRAM,
+};
+
+#[derive(Accounts)]
+pub struct UpdateCollection<'info> {
+    /// The one who pays for the transaction(i.e updating the metadata)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The collection whose metadata is being updated.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+
+    /// The update authority or update delegate of the collection.
+    pub authority: Option<Signer<'info>>,
+
+    /// The config of a program-controlled collection, see `create_program_collection`.
+    /// When present, `authority` must be the config authority and the collection authority PDA signs.
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+
+    /// The collection authority PDA of `collection_config`.
+    /// CHECK: Checked against the collection config.
+    pub collection_authority: Option<AccountInfo<'info>>,
+
+    /// The new update authority, handed over immediately.
+    /// Prefer `propose_collection_update_authority` unless the address is certain.
+    /// CHECK: Checked in mpl-core.
+    pub new_update_authority: Option<AccountInfo<'info>>,
+
+    /// The system program.
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+/// Fields left as `None` are not changed.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateCollectionArgs {
+    pub name: Option<String>,
+    pub uri: Option<String>,
+}
+
+impl<'info> UpdateCollection<'info> {
+    pub fn update_collection(&mut self, args: UpdateCollectionArgs) -> Result<()> {
+        if let Some(name) = &args.name {
+            require!(!name.is_empty(), ErrorCode::EmptyName);
+        }
+        validate_optional_metadata(args.name.as_deref(), args.uri.as_deref())?;
+        load_collection(&self.collection)?;
+
+        let mut builder = UpdateCollectionV1CpiBuilder::new(&self.mpl_core);
+        builder
+            .collection(&self.collection)
+            .payer(self.payer.as_ref())
+            .new_update_authority(self.new_update_authority.as_ref())
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref());
+        if let Some(name) = args.name {
+            builder.new_name(name);
+        }
+        if let Some(uri) = args.uri {
+            builder.new_uri(uri);
+        }
+        invoke_as_collection_authority(
+            self.collection_config.as_ref(),
+            Some(&self.collection),
+            self.authority.as_deref(),
+            self.collection_authority.as_ref(),
+            |authority, signer_seeds| builder.authority(authority).invoke_signed(signer_seeds),
+        )
+    }
+}
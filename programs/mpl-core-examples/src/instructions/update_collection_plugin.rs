@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+use mpl_core::{
+    fetch_collection_plugin,
+    instructions::UpdateCollectionPluginV1CpiBuilder,
+    types::{PermanentFreezeDelegate, Plugin, PluginType, Royalties},
+};
+
+use crate::{
+    error::ErrorCode,
+    royalties::{to_creators, validate_royalties, validate_rule_set},
+    state::CollectionConfig,
+    validation::{
+        invoke_as_collection_authority, load_collection, validate_collection_plugin_authority,
+    },
+    UpdateRoyaltiesPluginArgs, UpdateRoyaltiesRuleSetArgs, SPL_NOOP_PROGRAM,
+};
+
+/// Collection-level counterpart of `UpdateNFTPlugin`.
+/// Plugins on a collection apply to every asset in it, so updating them here
+/// updates the whole collection at once.
+#[derive(Accounts)]
+pub struct UpdateCollectionPlugin<'info> {
+    /// The address of the collection.
+    /// CHECK: Checked in mpl-core.
+    #[account(mut)]
+    pub collection: AccountInfo<'info>,
+
+    /// The account paying for the storage fees.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The update authority or delegate of the collection.
+    pub authority: Option<Signer<'info>>,
+
+    /// The config of a program-controlled collection, see `create_program_collection`.
+    /// When present, `authority` must be the config authority and the collection authority PDA signs.
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+
+    /// The collection authority PDA of `collection_config`.
+    /// CHECK: Checked against the collection config.
+    pub collection_authority: Option<AccountInfo<'info>>,
+
+    /// The system program.
+    pub system_program: Program<'info, System>,
+
+    /// The SPL Noop program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address=SPL_NOOP_PROGRAM)]
+    pub log_wrapper: Option<AccountInfo<'info>>,
+
+    /// The MPL Core program.
+    /// CHECK: Checked in mpl-core.
+    #[account(address = mpl_core::ID)]
+    pub mpl_core: AccountInfo<'info>,
+}
+
+impl<'info> UpdateCollectionPlugin<'info> {
+    /// Replaces the royalties of the collection, which every asset without its own
+    /// royalties plugin inherits.
+    pub fn update_royalties_plugin(&mut self, args: UpdateRoyaltiesPluginArgs) -> Result<()> {
+        let rule_set = args.rule_set.unwrap_or_default();
+        validate_royalties(args.basis_points, &args.creators)?;
+        validate_rule_set(&rule_set)?;
+        load_collection(&self.collection)?;
+
+        // Only presence is checked, but mpl-core still deserializes the whole plugin
+        fetch_collection_plugin::<()>(&self.collection, PluginType::Royalties)
+            .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        self.update(Plugin::Royalties(Royalties {
+            basis_points: args.basis_points,
+            creators: to_creators(args.creators),
+            rule_set: rule_set.into(),
+        }))
+    }
+
+    /// Replaces only the rule set of the collection's royalties plugin.
+    /// The existing basis points and creators are read from the collection and sent back untouched.
+    pub fn update_royalties_rule_set(&mut self, args: UpdateRoyaltiesRuleSetArgs) -> Result<()> {
+        validate_rule_set(&args.rule_set)?;
+        load_collection(&self.collection)?;
+
+        let (_, royalties, _) =
+            fetch_collection_plugin::<Royalties>(&self.collection, PluginType::Royalties)
+                .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        self.update(Plugin::Royalties(Royalties {
+            rule_set: args.rule_set.into(),
+            ..royalties
+        }))
+    }
+
+    /// Toggles the permanent freeze delegate of the collection.
+    /// mpl-core checks the collection's plugin for every member asset,
+    /// so this freezes or thaws all of them in a single instruction.
+    pub fn update_permanent_freeze_delegate(&mut self) -> Result<()> {
+        load_collection(&self.collection)?;
+
+        let (plugin_authority, permanent_freeze, _) =
+            fetch_collection_plugin::<PermanentFreezeDelegate>(
+                &self.collection,
+                PluginType::PermanentFreezeDelegate,
+            )
+            .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        // mpl-core falls back to the payer when no authority is passed,
+        // and the collection authority PDA signs for a program-controlled collection
+        let signer = match (&self.collection_config, &self.collection_authority) {
+            (Some(_), Some(collection_authority)) => collection_authority.key(),
+            _ => self.authority.as_ref().unwrap_or(&self.payer).key(),
+        };
+        validate_collection_plugin_authority(&plugin_authority, &signer)?;
+
+        self.update(Plugin::PermanentFreezeDelegate(PermanentFreezeDelegate {
+            frozen: !permanent_freeze.frozen,
+        }))
+    }
+
+    fn update(&self, plugin: Plugin) -> Result<()> {
+        let mut builder = UpdateCollectionPluginV1CpiBuilder::new(&self.mpl_core);
+        builder
+            .payer(self.payer.as_ref())
+            .collection(&self.collection)
+            .system_program(self.system_program.as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin(plugin);
+        invoke_as_collection_authority(
+            self.collection_config.as_ref(),
+            Some(&self.collection),
+            self.authority.as_deref(),
+            self.collection_authority.as_ref(),
+            |authority, signer_seeds| builder.authority(authority).invoke_signed(signer_seeds),
+        )
+    }
+}
@@ -1,7 +1,11 @@
 use anchor_lang::prelude::*;
 use mpl_core::instructions::UpdateV1CpiBuilder;
 
-use crate::MPL_CORE_ID;
+use crate::{
+    state::CollectionConfig,
+    validation::{invoke_as_collection_authority, validate_asset_collection, validate_metadata},
+    MPL_CORE_ID,
+};
 
 #[derive(Accounts)]
 pub struct UpdateNFTMetadata<'info> {
@@ -23,6 +27,14 @@ pub struct UpdateNFTMetadata<'info> {
     /// CHECK: Checked in mpl core
     pub update_authority: Option<AccountInfo<'info>>,
 
+    /// The config of a program-controlled collection, see `create_program_collection`.
+    /// When present, `update_authority` must be the config authority and the collection authority PDA signs.
+    pub collection_config: Option<Account<'info, CollectionConfig>>,
+
+    /// The collection authority PDA of `collection_config`.
+    /// CHECK: Checked against the collection config.
+    pub collection_authority: Option<AccountInfo<'info>>,
+
     /// CHECK: The address constraint is used to ensure this is the mpl core program
     #[account(address=MPL_CORE_ID)]
     pub mpl_core_program: AccountInfo<'info>,
@@ -38,15 +50,23 @@ pub struct UpdateNFTMetadataArgs {
 
 impl<'info> UpdateNFTMetadata<'info> {
     pub fn handler(&mut self, args: UpdateNFTMetadataArgs) -> Result<()> {
-        UpdateV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
-            .asset(self.asset.to_account_info().as_ref())
+        validate_metadata(&args.name, &args.uri)?;
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
+        let mut builder = UpdateV1CpiBuilder::new(&self.mpl_core_program);
+        builder
+            .asset(&self.asset)
             .collection(self.collection.as_ref())
             .new_name(args.name)
             .new_uri(args.uri)
-            .authority(self.update_authority.as_ref())
-            .payer(self.payer.to_account_info().as_ref())
-            .system_program(self.system_program.to_account_info().as_ref())
-            .invoke()?;
-        Ok(())
+            .payer(self.payer.as_ref())
+            .system_program(self.system_program.as_ref());
+        invoke_as_collection_authority(
+            self.collection_config.as_ref(),
+            self.collection.as_ref(),
+            self.update_authority.as_ref(),
+            self.collection_authority.as_ref(),
+            |authority, signer_seeds| builder.authority(authority).invoke_signed(signer_seeds),
+        )
     }
 }
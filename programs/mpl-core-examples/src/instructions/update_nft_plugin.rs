@@ -3,16 +3,20 @@ use mpl_core::{
     fetch_asset_plugin,
     instructions::UpdatePluginV1CpiBuilder,
     types::{
-        AutographSignature, Creator, FreezeDelegate, Plugin, PluginType, Royalties,
+        Autograph, AutographSignature, FreezeDelegate, Plugin, PluginType, Royalties,
     },
 };
 
-use crate::{CreatorArgs, SPL_NOOP_PROGRAM};
+use crate::{
+    error::ErrorCode,
+    royalties::{to_creators, validate_royalties, validate_rule_set, RuleSetArgs},
+    validation::{validate_asset_collection, validate_autograph_message, validate_plugin_authority},
+    CreatorArgs, SPL_NOOP_PROGRAM,
+};
 
 /// Updating an existing asset/NFT can be done on the client side using UMI
 /// But here I take some examples of instances where you can update plugins from an anchor program
-/// The same can be done for collections as well, it's just a matter of passing the collection address instead of the asset address
-/// and then chainging the CPI name from `UpdatePluginV1CpiBuilder` to `UpdateCollectionPluginV1CpiBuilder`
+/// The same can be done for collections with `UpdateCollectionPluginV1CpiBuilder`, see `UpdateCollectionPlugin`
 #[derive(Accounts)]
 pub struct UpdateNFTPlugin<'info> {
     /// The address of the asset.
@@ -50,6 +54,14 @@ pub struct UpdateNFTPlugin<'info> {
 pub struct UpdateRoyaltiesPluginArgs {
     pub basis_points: u16,
     pub creators: Vec<CreatorArgs>,
+    /// Which programs can transfer the asset. Defaults to `RuleSetArgs::None`,
+    /// use `update_royalties_rule_set` to change only the rule set.
+    pub rule_set: Option<RuleSetArgs>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateRoyaltiesRuleSetArgs {
+    pub rule_set: RuleSetArgs,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -60,15 +72,12 @@ pub struct UpdateAutographPluginArgs {
 impl<'info> UpdateNFTPlugin<'info> {
     /// Example of updating the royalties plugin of an existing NFT
     pub fn update_royalties_plugin(&mut self, args: UpdateRoyaltiesPluginArgs) -> Result<()> {
-        // Map the incoming creators into the expected `Creator` format.
-        let creators = args
-            .creators
-            .into_iter()
-            .map(|c| Creator {
-                address: c.address,
-                percentage: c.percentage,
-            })
-            .collect();
+        let rule_set = args.rule_set.unwrap_or_default();
+        validate_royalties(args.basis_points, &args.creators)?;
+        validate_rule_set(&rule_set)?;
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
+        let creators = to_creators(args.creators);
 
         UpdatePluginV1CpiBuilder::new(&self.mpl_core)
             .payer(self.payer.to_account_info().as_ref())
@@ -80,7 +89,32 @@ impl<'info> UpdateNFTPlugin<'info> {
             .plugin(Plugin::Royalties(Royalties {
                 basis_points: args.basis_points,
                 creators,
-                rule_set: mpl_core::types::RuleSet::None,
+                rule_set: rule_set.into(),
+            }))
+            .invoke()?;
+        Ok(())
+    }
+
+    /// Replaces only the rule set of the royalties plugin, e.g. to block a marketplace.
+    /// The existing basis points and creators are read from the asset and sent back untouched.
+    pub fn update_royalties_rule_set(&mut self, args: UpdateRoyaltiesRuleSetArgs) -> Result<()> {
+        validate_rule_set(&args.rule_set)?;
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
+        let (_, royalties, _) =
+            fetch_asset_plugin::<Royalties>(self.asset.as_ref(), PluginType::Royalties)
+                .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        UpdatePluginV1CpiBuilder::new(&self.mpl_core)
+            .payer(self.payer.to_account_info().as_ref())
+            .asset(self.asset.as_ref())
+            .collection(self.collection.as_ref())
+            .authority(self.authority.as_deref())
+            .system_program(self.system_program.to_account_info().as_ref())
+            .log_wrapper(self.log_wrapper.as_ref())
+            .plugin(Plugin::Royalties(Royalties {
+                rule_set: args.rule_set.into(),
+                ..royalties
             }))
             .invoke()?;
         Ok(())
@@ -88,9 +122,16 @@ impl<'info> UpdateNFTPlugin<'info> {
 
     /// Let's try an example of updating a state based plugin like freeze delegate where frozen can be set to true or false
     pub fn update_freeze_delegate(&mut self) -> Result<()> {
+        let asset = validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
         // First we need to fetch the existing plugin data to know the current state
-        let (_, freeze_delegate, __) =
-            fetch_asset_plugin::<FreezeDelegate>(self.asset.as_ref(), PluginType::FreezeDelegate)?;
+        let (plugin_authority, freeze_delegate, _) =
+            fetch_asset_plugin::<FreezeDelegate>(self.asset.as_ref(), PluginType::FreezeDelegate)
+                .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        // mpl-core falls back to the payer when no authority is passed
+        let signer = self.authority.as_ref().unwrap_or(&self.payer).key();
+        validate_plugin_authority(&plugin_authority, &asset, &signer)?;
 
         UpdatePluginV1CpiBuilder::new(&self.mpl_core)
             .payer(self.payer.to_account_info().as_ref())
@@ -112,20 +153,42 @@ impl<'info> UpdateNFTPlugin<'info> {
     /// you'll need to pass the complete list you want to maintain when updating these plugins
     /// since it maintains the full list of data.
     ///
+    /// The `authority` is the autographer: it must sign, and mpl-core only lets a non-owner
+    /// append a signature under its own address. Each address can sign an asset once.
+    ///
     /// See example below
-    pub fn add_new_autograph_to_asset_with_existing_autograph_plugin(&mut self, args: UpdateAutographPluginArgs) -> Result<()> {
+    pub fn add_new_autograph_to_asset_with_existing_autograph_plugin(
+        &mut self,
+        args: UpdateAutographPluginArgs,
+    ) -> Result<()> {
+        validate_autograph_message(&args.message)?;
+        validate_asset_collection(&self.asset, self.collection.as_ref())?;
+
+        let autographer = self
+            .authority
+            .as_ref()
+            .ok_or(ErrorCode::MissingAutographer)?
+            .key();
+
         // Fetch the existing autograph plugin data
-        let (_, mut existing_autograph_plugin, __) = fetch_asset_plugin::<
-            mpl_core::types::Autograph,
-        >(
-            self.asset.as_ref(), PluginType::Autograph
-        )?;
+        let (_, mut existing_autograph_plugin, _) =
+            fetch_asset_plugin::<Autograph>(self.asset.as_ref(), PluginType::Autograph)
+                .map_err(|_| error!(ErrorCode::PluginNotFound))?;
+
+        require!(
+            !existing_autograph_plugin
+                .signatures
+                .iter()
+                .any(|signature| signature.address == autographer),
+            ErrorCode::DuplicateAutograph
+        );
+
         // Add a new signature to the existing list and return it as the updated plugin data
         let updated_autograph_plugin = {
             existing_autograph_plugin
                 .signatures
                 .push(AutographSignature {
-                    address: self.payer.key(),
+                    address: autographer,
                     message: args.message,
                 });
             existing_autograph_plugin
@@ -139,7 +202,8 @@ impl<'info> UpdateNFTPlugin<'info> {
             .authority(self.authority.as_deref())
             .system_program(self.system_program.to_account_info().as_ref())
             .log_wrapper(self.log_wrapper.as_ref())
-            .plugin(Plugin::Autograph(updated_autograph_plugin));
+            .plugin(Plugin::Autograph(updated_autograph_plugin))
+            .invoke()?;
         Ok(())
     }
 }
@@ -1,10 +1,17 @@
 pub mod constants;
 pub mod error;
 pub mod instructions;
+pub mod merkle;
+pub mod plugins;
+pub mod royalties;
+pub mod settlement;
 pub mod state;
+pub mod validation;
 
 pub use constants::*;
 pub use instructions::*;
+use merkle::AllowlistProof;
+pub use plugins::*;
 
 declare_id!("5s9UNbsAjeJPfoSBdDNUZuGn89orurbmfDVJXuY4sW7d");
 
@@ -50,6 +57,64 @@ pub mod mpl_core_examples {
         ctx.accounts.create_collection_with_oracle_plugin(args)
     }
 
+    pub fn create_collection_with_plugins(
+        ctx: Context<CreateCollection>,
+        args: CreateCollectionWithPluginsArgs,
+    ) -> Result<()> {
+        ctx.accounts.create_collection_with_plugins(args)
+    }
+
+    pub fn create_program_collection(
+        ctx: Context<CreateProgramCollection>,
+        args: CreateCollectionWithPluginsArgs,
+    ) -> Result<()> {
+        ctx.accounts.create_program_collection(args, &ctx.bumps)
+    }
+
+    pub fn set_collection_mint_limit(
+        ctx: Context<UpdateCollectionConfig>,
+        args: SetMintLimitArgs,
+    ) -> Result<()> {
+        ctx.accounts.set_mint_limit(args)
+    }
+
+    pub fn set_collection_allowlist_root(
+        ctx: Context<UpdateCollectionConfig>,
+        args: SetAllowlistRootArgs,
+    ) -> Result<()> {
+        ctx.accounts.set_allowlist_root(args)
+    }
+
+    pub fn start_collection_mint_phase(ctx: Context<UpdateCollectionConfig>) -> Result<()> {
+        ctx.accounts.start_mint_phase()
+    }
+
+    pub fn initialize_mint_config(
+        ctx: Context<InitializeMintConfig>,
+        args: MintConfigArgs,
+    ) -> Result<()> {
+        ctx.accounts
+            .initialize_mint_config(args, ctx.bumps.mint_config)
+    }
+
+    pub fn update_mint_phases(
+        ctx: Context<UpdateMintPhases>,
+        args: UpdateMintPhasesArgs,
+    ) -> Result<()> {
+        ctx.accounts.update_mint_phases(args)
+    }
+
+    pub fn update_mint_config(
+        ctx: Context<UpdateMintConfig>,
+        args: UpdateMintConfigArgs,
+    ) -> Result<()> {
+        ctx.accounts.update_mint_config(args)
+    }
+
+    pub fn mint_from_config(ctx: Context<MintFromConfig>, args: MintFromConfigArgs) -> Result<()> {
+        ctx.accounts.mint_from_config(args, &ctx.bumps)
+    }
+
     pub fn create_master_edition(
         ctx: Context<CreateCollection>,
         args: CreateMasterEditionArgs,
@@ -61,6 +126,14 @@ pub mod mpl_core_examples {
         ctx.accounts.create_edition(args)
     }
 
+    pub fn create_edition_from_allowlist(
+        ctx: Context<CreateEdition>,
+        args: CreateEditionArgs,
+        proof: AllowlistProof,
+    ) -> Result<()> {
+        ctx.accounts.create_edition_from_allowlist(args, proof)
+    }
+
     pub fn create_nft(ctx: Context<CreateNFT>, args: CreateNFTArgs) -> Result<()> {
         ctx.accounts.create_nft(args)
     }
@@ -87,6 +160,21 @@ pub mod mpl_core_examples {
         ctx.accounts.create_nft_with_permanent_burn_delegate(args)
     }
 
+    pub fn create_nft_with_plugins(
+        ctx: Context<CreateNFT>,
+        args: CreateNFTWithPluginsArgs,
+    ) -> Result<()> {
+        ctx.accounts.create_nft_with_plugins(args)
+    }
+
+    pub fn create_nft_from_allowlist(
+        ctx: Context<CreateNFT>,
+        args: CreateNFTWithPluginsArgs,
+        proof: AllowlistProof,
+    ) -> Result<()> {
+        ctx.accounts.create_nft_from_allowlist(args, proof)
+    }
+
     pub fn transfer_nft(ctx: Context<TransferNFT>) -> Result<()> {
         ctx.accounts.handler()
     }
@@ -95,6 +183,10 @@ pub mod mpl_core_examples {
         ctx.accounts.burn_nft()
     }
 
+    pub fn burn_collection(ctx: Context<BurnCollection>) -> Result<()> {
+        ctx.accounts.burn_collection()
+    }
+
     pub fn update_nft_metadata(
         ctx: Context<UpdateNFTMetadata>,
         args: UpdateNFTMetadataArgs,
@@ -103,6 +195,33 @@ pub mod mpl_core_examples {
     }
 
 
+    pub fn move_nft(ctx: Context<MoveNFT>, args: MoveNFTArgs) -> Result<()> {
+        ctx.accounts.move_nft(args)
+    }
+
+    pub fn update_collection(
+        ctx: Context<UpdateCollection>,
+        args: UpdateCollectionArgs,
+    ) -> Result<()> {
+        ctx.accounts.update_collection(args)
+    }
+
+    pub fn propose_collection_update_authority(
+        ctx: Context<ProposeUpdateAuthority>,
+        args: ProposeUpdateAuthorityArgs,
+    ) -> Result<()> {
+        ctx.accounts
+            .propose_update_authority(args, ctx.bumps.proposal)
+    }
+
+    pub fn accept_collection_update_authority(ctx: Context<AcceptUpdateAuthority>) -> Result<()> {
+        ctx.accounts.accept_update_authority()
+    }
+
+    pub fn cancel_collection_update_authority(ctx: Context<CancelUpdateAuthority>) -> Result<()> {
+        ctx.accounts.cancel_update_authority()
+    }
+
     pub fn add_royalties_plugin(
         ctx: Context<AddPluginsToNft>,
         args: AddRoyaltiesPluginArgs,
@@ -117,6 +236,34 @@ pub mod mpl_core_examples {
         ctx.accounts.add_autograph_plugin_to_asset(args)
     }
 
+    pub fn add_transfer_delegate_plugin(
+        ctx: Context<AddPluginsToNft>,
+        args: AddDelegatePluginArgs,
+    ) -> Result<()> {
+        ctx.accounts.add_transfer_delegate_plugin_to_asset(args)
+    }
+
+    pub fn add_freeze_delegate_plugin(
+        ctx: Context<AddPluginsToNft>,
+        args: AddDelegatePluginArgs,
+    ) -> Result<()> {
+        ctx.accounts.add_freeze_delegate_plugin_to_asset(args)
+    }
+
+    pub fn add_burn_delegate_plugin(
+        ctx: Context<AddPluginsToNft>,
+        args: AddDelegatePluginArgs,
+    ) -> Result<()> {
+        ctx.accounts.add_burn_delegate_plugin_to_asset(args)
+    }
+
+    pub fn add_royalties_plugin_to_collection(
+        ctx: Context<AddPluginsToCollections>,
+        args: AddRoyaltiesPluginArgs,
+    ) -> Result<()> {
+        ctx.accounts.add_royalties_plugin_to_collection(args)
+    }
+
     pub fn add_metaplex_oracle_to_collection(ctx: Context<AddPluginsToCollections>) -> Result<()> {
         ctx.accounts.add_metaplex_oracle_to_collection()
     }
@@ -129,10 +276,37 @@ pub mod mpl_core_examples {
         ctx.accounts.update_royalties_plugin(args)
     }
 
+    pub fn update_royalties_rule_set(
+        ctx: Context<UpdateNFTPlugin>,
+        args: UpdateRoyaltiesRuleSetArgs,
+    ) -> Result<()> {
+        ctx.accounts.update_royalties_rule_set(args)
+    }
+
+    pub fn update_collection_royalties_plugin(
+        ctx: Context<UpdateCollectionPlugin>,
+        args: UpdateRoyaltiesPluginArgs,
+    ) -> Result<()> {
+        ctx.accounts.update_royalties_plugin(args)
+    }
+
+    pub fn update_collection_royalties_rule_set(
+        ctx: Context<UpdateCollectionPlugin>,
+        args: UpdateRoyaltiesRuleSetArgs,
+    ) -> Result<()> {
+        ctx.accounts.update_royalties_rule_set(args)
+    }
+
     pub fn update_freeze_plugin(ctx: Context<UpdateNFTPlugin>) -> Result<()> {
         ctx.accounts.update_freeze_delegate()
     }
 
+    pub fn update_collection_permanent_freeze_plugin(
+        ctx: Context<UpdateCollectionPlugin>,
+    ) -> Result<()> {
+        ctx.accounts.update_permanent_freeze_delegate()
+    }
+
     pub fn add_new_autograph_to_asset_with_existing_autograph_plugin(
         ctx: Context<UpdateNFTPlugin>,
         args:UpdateAutographPluginArgs
@@ -141,6 +315,168 @@ pub mod mpl_core_examples {
             .add_new_autograph_to_asset_with_existing_autograph_plugin(args)
     }
 
+    pub fn remove_own_autograph(ctx: Context<RemoveAutograph>) -> Result<()> {
+        ctx.accounts.remove_own_autograph()
+    }
+
+    pub fn remove_nft_plugin(ctx: Context<RemoveNFTPlugin>, args: RemovePluginArgs) -> Result<()> {
+        ctx.accounts.remove_plugin(args)
+    }
+
+    pub fn remove_nft_external_plugin_adapter(
+        ctx: Context<RemoveNFTPlugin>,
+        args: RemoveExternalPluginAdapterArgs,
+    ) -> Result<()> {
+        ctx.accounts.remove_external_plugin_adapter(args)
+    }
+
+    pub fn remove_collection_plugin(
+        ctx: Context<RemoveCollectionPlugin>,
+        args: RemovePluginArgs,
+    ) -> Result<()> {
+        ctx.accounts.remove_plugin(args)
+    }
+
+    pub fn remove_collection_external_plugin_adapter(
+        ctx: Context<RemoveCollectionPlugin>,
+        args: RemoveExternalPluginAdapterArgs,
+    ) -> Result<()> {
+        ctx.accounts.remove_external_plugin_adapter(args)
+    }
+
+    pub fn approve_nft_plugin_authority(
+        ctx: Context<NFTPluginAuthority>,
+        args: ApprovePluginAuthorityArgs,
+    ) -> Result<()> {
+        ctx.accounts.approve_plugin_authority(args)
+    }
+
+    pub fn revoke_nft_plugin_authority(
+        ctx: Context<NFTPluginAuthority>,
+        args: RevokePluginAuthorityArgs,
+    ) -> Result<()> {
+        ctx.accounts.revoke_plugin_authority(args)
+    }
+
+    pub fn revoke_all_owner_managed_delegates(ctx: Context<NFTPluginAuthority>) -> Result<()> {
+        ctx.accounts.revoke_all_owner_managed_delegates()
+    }
+
+    pub fn thaw_with_program_delegate(ctx: Context<ProgramDelegate>) -> Result<()> {
+        ctx.accounts.thaw(ctx.bumps.program_delegate)
+    }
+
+    pub fn revoke_program_delegate(
+        ctx: Context<ProgramDelegate>,
+        args: RevokePluginAuthorityArgs,
+    ) -> Result<()> {
+        ctx.accounts.revoke(args, ctx.bumps.program_delegate)
+    }
+
+    pub fn revoke_collection_program_delegate(
+        ctx: Context<CollectionProgramDelegate>,
+        args: RevokePluginAuthorityArgs,
+    ) -> Result<()> {
+        ctx.accounts.revoke(args, ctx.bumps.program_delegate)
+    }
+
+    pub fn approve_collection_plugin_authority(
+        ctx: Context<CollectionPluginAuthority>,
+        args: ApprovePluginAuthorityArgs,
+    ) -> Result<()> {
+        ctx.accounts.approve_plugin_authority(args)
+    }
+
+    pub fn revoke_collection_plugin_authority(
+        ctx: Context<CollectionPluginAuthority>,
+        args: RevokePluginAuthorityArgs,
+    ) -> Result<()> {
+        ctx.accounts.revoke_plugin_authority(args)
+    }
+
+    pub fn initialize_stake_pool(
+        ctx: Context<InitializeStakePool>,
+        args: InitializeStakePoolArgs,
+    ) -> Result<()> {
+        ctx.accounts
+            .initialize_stake_pool(args, ctx.bumps.stake_pool)
+    }
+
+    pub fn initialize_reward_vault(ctx: Context<InitializeRewardVault>) -> Result<()> {
+        ctx.accounts.initialize_reward_vault()
+    }
+
+    pub fn fund_reward_vault(
+        ctx: Context<FundRewardVault>,
+        args: FundRewardVaultArgs,
+    ) -> Result<()> {
+        ctx.accounts.fund_reward_vault(args)
+    }
+
+    pub fn set_reward_rate(ctx: Context<UpdateStakePool>, args: SetRewardRateArgs) -> Result<()> {
+        ctx.accounts.set_reward_rate(args)
+    }
+
+    pub fn set_reward_multipliers(
+        ctx: Context<UpdateStakePool>,
+        args: SetRewardMultipliersArgs,
+    ) -> Result<()> {
+        ctx.accounts.set_reward_multipliers(args)
+    }
+
+    pub fn stake(ctx: Context<Stake>) -> Result<()> {
+        ctx.accounts.stake(ctx.bumps.stake_record)
+    }
+
+    pub fn unstake(ctx: Context<Unstake>) -> Result<()> {
+        ctx.accounts.unstake()
+    }
+
+    pub fn claim_staking_rewards(ctx: Context<ClaimStakingRewards>) -> Result<()> {
+        ctx.accounts.claim_staking_rewards()
+    }
+
+    pub fn claim_pending_rewards(ctx: Context<ClaimPendingRewards>) -> Result<()> {
+        ctx.accounts.claim_pending_rewards()
+    }
+
+    pub fn list(ctx: Context<List>, args: ListArgs) -> Result<()> {
+        ctx.accounts.list(args, ctx.bumps.listing)
+    }
+
+    pub fn delist(ctx: Context<Delist>) -> Result<()> {
+        ctx.accounts.delist()
+    }
+
+    pub fn buy<'info>(ctx: Context<'_, '_, 'info, 'info, Buy<'info>>) -> Result<()> {
+        ctx.accounts.buy(ctx.remaining_accounts)
+    }
+
+    pub fn settle_royalties<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleRoyalties<'info>>,
+        args: SettleRoyaltiesArgs,
+    ) -> Result<()> {
+        ctx.accounts.settle_royalties(args, ctx.remaining_accounts)
+    }
+
+    pub fn make_offer(ctx: Context<MakeOffer>, args: MakeOfferArgs) -> Result<()> {
+        ctx.accounts.make_offer(args, ctx.bumps.offer)
+    }
+
+    pub fn accept_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AcceptOffer<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.accept_offer(ctx.remaining_accounts)
+    }
+
+    pub fn cancel_offer(ctx: Context<CancelOffer>) -> Result<()> {
+        ctx.accounts.cancel_offer()
+    }
+
+    pub fn refund_expired_offer(ctx: Context<RefundExpiredOffer>) -> Result<()> {
+        ctx.accounts.refund_expired_offer()
+    }
+
     pub fn add_metaplex_oracle_to_nft(ctx: Context<AddPluginsToNft>)->Result<()> {
         ctx.accounts.add_metaplex_oracle_to_nft()
     }
@@ -0,0 +1,142 @@
+use anchor_lang::{prelude::*, solana_program::hash::hashv};
+
+/// Domain separators, so an inner node can never be passed off as a leaf.
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Proof that a wallet is on the allowlist of a collection.
+/// Every leaf of the tree is `sha256(0x00 || wallet || allocation as u32 LE)` and every
+/// node is `sha256(0x01 || min(left, right) || max(left, right))`, so the proof is just the
+/// sibling hashes from the leaf up.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AllowlistProof {
+    /// How many assets the wallet can mint per phase.
+    pub allocation: u32,
+    pub proof: Vec<[u8; 32]>,
+}
+
+/// Hash of the allowlist entry of `wallet`.
+pub fn allowlist_leaf(wallet: &Pubkey, allocation: u32) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, wallet.as_ref(), &allocation.to_le_bytes()]).to_bytes()
+}
+
+/// Hashes a pair of nodes. Sorting them makes the proof independent of the leaf position.
+pub fn hash_nodes(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Checks that `leaf` is part of the tree with the given `root`.
+pub fn verify_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_nodes(&node, sibling));
+    computed == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds every level of the tree, from the leaves to the root.
+    /// An odd node out is carried up to the next level unchanged.
+    fn build_tree(leaves: &[[u8; 32]]) -> Vec<Vec<[u8; 32]>> {
+        let mut levels = vec![leaves.to_vec()];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_nodes(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        levels
+    }
+
+    fn proof_for(levels: &[Vec<[u8; 32]>], mut index: usize) -> Vec<[u8; 32]> {
+        let mut proof = vec![];
+        for level in &levels[..levels.len() - 1] {
+            let sibling = index ^ 1;
+            if sibling < level.len() {
+                proof.push(level[sibling]);
+            }
+            index /= 2;
+        }
+        proof
+    }
+
+    fn allowlist(size: usize) -> Vec<(Pubkey, u32)> {
+        (0..size)
+            .map(|index| (Pubkey::new_unique(), index as u32 + 1))
+            .collect()
+    }
+
+    fn leaves(entries: &[(Pubkey, u32)]) -> Vec<[u8; 32]> {
+        entries
+            .iter()
+            .map(|(wallet, allocation)| allowlist_leaf(wallet, *allocation))
+            .collect()
+    }
+
+    #[test]
+    fn verifies_every_leaf_of_generated_trees() {
+        for size in [1, 2, 3, 5, 8, 13, 32] {
+            let entries = allowlist(size);
+            let leaves = leaves(&entries);
+            let levels = build_tree(&leaves);
+            let root = levels.last().unwrap()[0];
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = proof_for(&levels, index);
+                assert!(
+                    verify_proof(&proof, &root, *leaf),
+                    "size {size} leaf {index}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_a_different_allocation() {
+        let entries = allowlist(8);
+        let levels = build_tree(&leaves(&entries));
+        let root = levels.last().unwrap()[0];
+
+        let (wallet, allocation) = entries[3];
+        let proof = proof_for(&levels, 3);
+        assert!(!verify_proof(
+            &proof,
+            &root,
+            allowlist_leaf(&wallet, allocation + 1)
+        ));
+    }
+
+    #[test]
+    fn rejects_a_wallet_that_is_not_listed() {
+        let entries = allowlist(5);
+        let levels = build_tree(&leaves(&entries));
+        let root = levels.last().unwrap()[0];
+
+        let proof = proof_for(&levels, 0);
+        assert!(!verify_proof(
+            &proof,
+            &root,
+            allowlist_leaf(&Pubkey::new_unique(), entries[0].1)
+        ));
+    }
+
+    #[test]
+    fn rejects_a_proof_against_a_rotated_root() {
+        let listed = leaves(&allowlist(4));
+        let levels = build_tree(&listed);
+        let rotated_root = build_tree(&leaves(&allowlist(4))).last().unwrap()[0];
+
+        let proof = proof_for(&levels, 2);
+        assert!(!verify_proof(&proof, &rotated_root, listed[2]));
+    }
+}
@@ -0,0 +1,599 @@
+use anchor_lang::prelude::*;
+use mpl_core::types::{
+    AddBlocker, AppDataInitInfo, Attribute, Attributes, BubblegumV2, BurnDelegate,
+    ExternalCheckResult, ExternalPluginAdapterInitInfo, ExternalPluginAdapterKey,
+    ExternalPluginAdapterSchema, FreezeDelegate, HookableLifecycleEvent, ImmutableMetadata,
+    LinkedAppDataInitInfo, MasterEdition, OracleInitInfo, PermanentBurnDelegate,
+    PermanentFreezeDelegate, PermanentTransferDelegate, Plugin, PluginAuthority,
+    PluginAuthorityPair, PluginType, Royalties, TransferDelegate, UpdateDelegate,
+    ValidationResultsOffset,
+};
+
+use crate::{
+    error::ErrorCode,
+    royalties::{to_creators, validate_royalties, validate_rule_set, RuleSetArgs},
+    validation::validate_optional_metadata,
+    CreatorArgs, DELEGATE_SEED,
+};
+
+/// Maximum number of plugins accepted by the composable create instructions.
+pub const MAX_PLUGINS: usize = 10;
+
+/// Maximum number of external plugin adapters accepted by the composable create instructions.
+pub const MAX_EXTERNAL_PLUGIN_ADAPTERS: usize = 4;
+
+/// Mirror of mpl-core's `PluginAuthority` that can be passed as an instruction argument.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum PluginAuthorityArgs {
+    /// Nobody can update the plugin, i.e. it becomes immutable.
+    None,
+    /// The owner of the asset.
+    Owner,
+    /// The update authority of the asset or collection.
+    UpdateAuthority,
+    /// A specific address, e.g. a delegate or a program PDA.
+    Address { address: Pubkey },
+}
+
+impl From<PluginAuthorityArgs> for PluginAuthority {
+    fn from(authority: PluginAuthorityArgs) -> Self {
+        match authority {
+            PluginAuthorityArgs::None => PluginAuthority::None,
+            PluginAuthorityArgs::Owner => PluginAuthority::Owner,
+            PluginAuthorityArgs::UpdateAuthority => PluginAuthority::UpdateAuthority,
+            PluginAuthorityArgs::Address { address } => PluginAuthority::Address { address },
+        }
+    }
+}
+
+/// Who receives the authority of a delegate plugin instead of the asset owner.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum DelegateArgs {
+    /// Any wallet or program address.
+    Address { address: Pubkey },
+    /// This program's delegate PDA (see [`program_delegate`]).
+    Program,
+}
+
+impl From<DelegateArgs> for PluginAuthority {
+    fn from(delegate: DelegateArgs) -> Self {
+        match delegate {
+            DelegateArgs::Address { address } => PluginAuthority::Address { address },
+            DelegateArgs::Program => PluginAuthority::Address {
+                address: program_delegate(),
+            },
+        }
+    }
+}
+
+/// The PDA of this program that can act as a plugin delegate.
+pub fn program_delegate() -> Pubkey {
+    Pubkey::find_program_address(&[DELEGATE_SEED.as_bytes()], &crate::ID).0
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AttributeArgs {
+    pub key: String,
+    pub value: String,
+}
+
+/// The plugins that can be attached through the composable create instructions.
+/// `Edition` is left out on purpose since editions are numbered by `create_edition`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum PluginArgs {
+    Royalties {
+        basis_points: u16,
+        creators: Vec<CreatorArgs>,
+        rule_set: RuleSetArgs,
+    },
+    FreezeDelegate {
+        frozen: bool,
+    },
+    BurnDelegate,
+    TransferDelegate,
+    UpdateDelegate {
+        additional_delegates: Vec<Pubkey>,
+    },
+    PermanentFreezeDelegate {
+        frozen: bool,
+    },
+    Attributes {
+        attribute_list: Vec<AttributeArgs>,
+    },
+    PermanentTransferDelegate,
+    PermanentBurnDelegate,
+    MasterEdition {
+        max_supply: Option<u32>,
+        name: Option<String>,
+        uri: Option<String>,
+    },
+    AddBlocker,
+    ImmutableMetadata,
+    BubblegumV2,
+}
+
+/// A plugin together with the authority it should be created with.
+/// When `authority` is `None` mpl-core picks the default authority for the plugin
+/// (the owner for owner-managed plugins and the update authority for the rest).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PluginSpec {
+    pub plugin: PluginArgs,
+    pub authority: Option<PluginAuthorityArgs>,
+}
+
+/// What the plugins are being attached to. Some plugins only make sense on one of them.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PluginTarget {
+    Asset,
+    Collection,
+}
+
+impl PluginArgs {
+    pub fn plugin_type(&self) -> PluginType {
+        match self {
+            PluginArgs::Royalties { .. } => PluginType::Royalties,
+            PluginArgs::FreezeDelegate { .. } => PluginType::FreezeDelegate,
+            PluginArgs::BurnDelegate => PluginType::BurnDelegate,
+            PluginArgs::TransferDelegate => PluginType::TransferDelegate,
+            PluginArgs::UpdateDelegate { .. } => PluginType::UpdateDelegate,
+            PluginArgs::PermanentFreezeDelegate { .. } => PluginType::PermanentFreezeDelegate,
+            PluginArgs::Attributes { .. } => PluginType::Attributes,
+            PluginArgs::PermanentTransferDelegate => PluginType::PermanentTransferDelegate,
+            PluginArgs::PermanentBurnDelegate => PluginType::PermanentBurnDelegate,
+            PluginArgs::MasterEdition { .. } => PluginType::MasterEdition,
+            PluginArgs::AddBlocker => PluginType::AddBlocker,
+            PluginArgs::ImmutableMetadata => PluginType::ImmutableMetadata,
+            PluginArgs::BubblegumV2 => PluginType::BubblegumV2,
+        }
+    }
+
+    /// Owner-managed plugins live on assets only.
+    pub fn is_owner_managed(&self) -> bool {
+        matches!(
+            self,
+            PluginArgs::FreezeDelegate { .. }
+                | PluginArgs::BurnDelegate
+                | PluginArgs::TransferDelegate
+        )
+    }
+
+    /// Plugins that mpl-core only accepts on collections.
+    pub fn is_collection_only(&self) -> bool {
+        matches!(
+            self,
+            PluginArgs::MasterEdition { .. } | PluginArgs::BubblegumV2
+        )
+    }
+
+    pub fn into_plugin(self) -> Plugin {
+        match self {
+            PluginArgs::Royalties {
+                basis_points,
+                creators,
+                rule_set,
+            } => Plugin::Royalties(Royalties {
+                basis_points,
+                creators: to_creators(creators),
+                rule_set: rule_set.into(),
+            }),
+            PluginArgs::FreezeDelegate { frozen } => {
+                Plugin::FreezeDelegate(FreezeDelegate { frozen })
+            }
+            PluginArgs::BurnDelegate => Plugin::BurnDelegate(BurnDelegate {}),
+            PluginArgs::TransferDelegate => Plugin::TransferDelegate(TransferDelegate {}),
+            PluginArgs::UpdateDelegate {
+                additional_delegates,
+            } => Plugin::UpdateDelegate(UpdateDelegate {
+                additional_delegates,
+            }),
+            PluginArgs::PermanentFreezeDelegate { frozen } => {
+                Plugin::PermanentFreezeDelegate(PermanentFreezeDelegate { frozen })
+            }
+            PluginArgs::Attributes { attribute_list } => Plugin::Attributes(Attributes {
+                attribute_list: attribute_list
+                    .into_iter()
+                    .map(|a| Attribute {
+                        key: a.key,
+                        value: a.value,
+                    })
+                    .collect(),
+            }),
+            PluginArgs::PermanentTransferDelegate => {
+                Plugin::PermanentTransferDelegate(PermanentTransferDelegate {})
+            }
+            PluginArgs::PermanentBurnDelegate => {
+                Plugin::PermanentBurnDelegate(PermanentBurnDelegate {})
+            }
+            PluginArgs::MasterEdition {
+                max_supply,
+                name,
+                uri,
+            } => Plugin::MasterEdition(MasterEdition {
+                max_supply,
+                name,
+                uri,
+            }),
+            PluginArgs::AddBlocker => Plugin::AddBlocker(AddBlocker {}),
+            PluginArgs::ImmutableMetadata => Plugin::ImmutableMetadata(ImmutableMetadata {}),
+            PluginArgs::BubblegumV2 => Plugin::BubblegumV2(BubblegumV2 {}),
+        }
+    }
+}
+
+/// Checks that the requested plugins can live together on the target and maps them
+/// into the `PluginAuthorityPair`s expected by the mpl-core create CPIs.
+pub fn build_plugin_authority_pairs(
+    specs: Vec<PluginSpec>,
+    target: PluginTarget,
+) -> Result<Vec<PluginAuthorityPair>> {
+    require!(specs.len() <= MAX_PLUGINS, ErrorCode::TooManyPlugins);
+
+    let mut seen: Vec<PluginType> = Vec::with_capacity(specs.len());
+    for spec in specs.iter() {
+        match target {
+            PluginTarget::Asset => require!(
+                !spec.plugin.is_collection_only(),
+                ErrorCode::PluginNotSupportedOnAsset
+            ),
+            PluginTarget::Collection => require!(
+                !spec.plugin.is_owner_managed(),
+                ErrorCode::PluginNotSupportedOnCollection
+            ),
+        }
+
+        // Owner-managed plugins can't be locked down since the owner must always be able to revoke them.
+        if spec.plugin.is_owner_managed() {
+            require!(
+                spec.authority != Some(PluginAuthorityArgs::None),
+                ErrorCode::InvalidPluginAuthority
+            );
+        }
+
+        if let PluginArgs::Royalties {
+            basis_points,
+            creators,
+            rule_set,
+        } = &spec.plugin
+        {
+            validate_royalties(*basis_points, creators)?;
+            validate_rule_set(rule_set)?;
+        }
+        if let PluginArgs::MasterEdition {
+            max_supply,
+            name,
+            uri,
+        } = &spec.plugin
+        {
+            require!(*max_supply != Some(0), ErrorCode::InvalidMaxSupply);
+            validate_optional_metadata(name.as_deref(), uri.as_deref())?;
+        }
+
+        let plugin_type = spec.plugin.plugin_type();
+        require!(!seen.contains(&plugin_type), ErrorCode::DuplicatePlugin);
+        seen.push(plugin_type);
+    }
+
+    Ok(specs
+        .into_iter()
+        .map(|spec| PluginAuthorityPair {
+            plugin: spec.plugin.into_plugin(),
+            authority: spec.authority.map(Into::into),
+        })
+        .collect())
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum HookableLifecycleEventArgs {
+    Create,
+    Transfer,
+    Burn,
+    Update,
+}
+
+impl From<HookableLifecycleEventArgs> for HookableLifecycleEvent {
+    fn from(event: HookableLifecycleEventArgs) -> Self {
+        match event {
+            HookableLifecycleEventArgs::Create => HookableLifecycleEvent::Create,
+            HookableLifecycleEventArgs::Transfer => HookableLifecycleEvent::Transfer,
+            HookableLifecycleEventArgs::Burn => HookableLifecycleEvent::Burn,
+            HookableLifecycleEventArgs::Update => HookableLifecycleEvent::Update,
+        }
+    }
+}
+
+/// The only `ExternalCheckResult` flags an oracle accepts: can reject.
+const ORACLE_CHECK_FLAGS: u32 = 4;
+
+/// A lifecycle event the adapter hooks into and the `ExternalCheckResult` flags for it
+/// (1 = can listen, 2 = can approve, 4 = can reject).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LifecycleCheckArgs {
+    pub event: HookableLifecycleEventArgs,
+    pub flags: u32,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum ExternalPluginAdapterSchemaArgs {
+    Binary,
+    Json,
+    MsgPack,
+}
+
+impl From<ExternalPluginAdapterSchemaArgs> for ExternalPluginAdapterSchema {
+    fn from(schema: ExternalPluginAdapterSchemaArgs) -> Self {
+        match schema {
+            ExternalPluginAdapterSchemaArgs::Binary => ExternalPluginAdapterSchema::Binary,
+            ExternalPluginAdapterSchemaArgs::Json => ExternalPluginAdapterSchema::Json,
+            ExternalPluginAdapterSchemaArgs::MsgPack => ExternalPluginAdapterSchema::MsgPack,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum ValidationResultsOffsetArgs {
+    NoOffset,
+    Anchor,
+    Custom(u64),
+}
+
+impl From<ValidationResultsOffsetArgs> for ValidationResultsOffset {
+    fn from(offset: ValidationResultsOffsetArgs) -> Self {
+        match offset {
+            ValidationResultsOffsetArgs::NoOffset => ValidationResultsOffset::NoOffset,
+            ValidationResultsOffsetArgs::Anchor => ValidationResultsOffset::Anchor,
+            ValidationResultsOffsetArgs::Custom(offset) => ValidationResultsOffset::Custom(offset),
+        }
+    }
+}
+
+/// The external plugin adapters that can be attached through the composable create instructions.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ExternalPluginAdapterArgs {
+    Oracle {
+        base_address: Pubkey,
+        lifecycle_checks: Vec<LifecycleCheckArgs>,
+        results_offset: Option<ValidationResultsOffsetArgs>,
+    },
+    AppData {
+        data_authority: PluginAuthorityArgs,
+        schema: Option<ExternalPluginAdapterSchemaArgs>,
+    },
+    LinkedAppData {
+        data_authority: PluginAuthorityArgs,
+        schema: Option<ExternalPluginAdapterSchemaArgs>,
+    },
+}
+
+/// An external plugin adapter together with the authority allowed to manage it.
+/// When `authority` is `None` it defaults to the update authority.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ExternalPluginAdapterSpec {
+    pub adapter: ExternalPluginAdapterArgs,
+    pub authority: Option<PluginAuthorityArgs>,
+}
+
+impl ExternalPluginAdapterArgs {
+    /// Linked adapters are declared on a collection and then apply to its assets.
+    pub fn is_collection_only(&self) -> bool {
+        matches!(self, ExternalPluginAdapterArgs::LinkedAppData { .. })
+    }
+
+    /// Two adapters with the same key can't be on the same account.
+    fn conflicts_with(&self, other: &ExternalPluginAdapterArgs) -> bool {
+        match (self, other) {
+            (
+                ExternalPluginAdapterArgs::Oracle { base_address, .. },
+                ExternalPluginAdapterArgs::Oracle {
+                    base_address: other_base_address,
+                    ..
+                },
+            ) => base_address == other_base_address,
+            (
+                ExternalPluginAdapterArgs::AppData { data_authority, .. },
+                ExternalPluginAdapterArgs::AppData {
+                    data_authority: other_data_authority,
+                    ..
+                },
+            )
+            | (
+                ExternalPluginAdapterArgs::LinkedAppData { data_authority, .. },
+                ExternalPluginAdapterArgs::LinkedAppData {
+                    data_authority: other_data_authority,
+                    ..
+                },
+            ) => data_authority == other_data_authority,
+            _ => false,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let ExternalPluginAdapterArgs::Oracle {
+            lifecycle_checks, ..
+        } = self
+        {
+            require!(
+                !lifecycle_checks.is_empty(),
+                ErrorCode::MissingLifecycleChecks
+            );
+            for (index, check) in lifecycle_checks.iter().enumerate() {
+                require!(
+                    !lifecycle_checks[..index]
+                        .iter()
+                        .any(|previous| previous.event == check.event),
+                    ErrorCode::DuplicateLifecycleCheck
+                );
+                // Oracles can only reject, mpl-core refuses any other check result
+                require!(
+                    check.flags == ORACLE_CHECK_FLAGS,
+                    ErrorCode::InvalidLifecycleCheckFlags
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn into_init_info(
+        self,
+        authority: Option<PluginAuthority>,
+    ) -> ExternalPluginAdapterInitInfo {
+        match self {
+            ExternalPluginAdapterArgs::Oracle {
+                base_address,
+                lifecycle_checks,
+                results_offset,
+            } => ExternalPluginAdapterInitInfo::Oracle(OracleInitInfo {
+                base_address,
+                init_plugin_authority: authority,
+                lifecycle_checks: lifecycle_checks
+                    .into_iter()
+                    .map(|check| {
+                        (
+                            check.event.into(),
+                            ExternalCheckResult { flags: check.flags },
+                        )
+                    })
+                    .collect(),
+                base_address_config: None,
+                results_offset: results_offset.map(Into::into),
+            }),
+            ExternalPluginAdapterArgs::AppData {
+                data_authority,
+                schema,
+            } => ExternalPluginAdapterInitInfo::AppData(AppDataInitInfo {
+                data_authority: data_authority.into(),
+                init_plugin_authority: authority,
+                schema: schema.map(Into::into),
+            }),
+            ExternalPluginAdapterArgs::LinkedAppData {
+                data_authority,
+                schema,
+            } => ExternalPluginAdapterInitInfo::LinkedAppData(LinkedAppDataInitInfo {
+                data_authority: data_authority.into(),
+                init_plugin_authority: authority,
+                schema: schema.map(Into::into),
+            }),
+        }
+    }
+}
+
+/// Same as [`build_plugin_authority_pairs`] but for external plugin adapters.
+pub fn build_external_plugin_adapters(
+    specs: Vec<ExternalPluginAdapterSpec>,
+    target: PluginTarget,
+) -> Result<Vec<ExternalPluginAdapterInitInfo>> {
+    require!(
+        specs.len() <= MAX_EXTERNAL_PLUGIN_ADAPTERS,
+        ErrorCode::TooManyPlugins
+    );
+
+    for (index, spec) in specs.iter().enumerate() {
+        if target == PluginTarget::Asset {
+            require!(
+                !spec.adapter.is_collection_only(),
+                ErrorCode::PluginNotSupportedOnAsset
+            );
+        }
+        spec.adapter.validate()?;
+        require!(
+            !specs[..index]
+                .iter()
+                .any(|previous| previous.adapter.conflicts_with(&spec.adapter)),
+            ErrorCode::DuplicatePlugin
+        );
+    }
+
+    Ok(specs
+        .into_iter()
+        .map(|spec| spec.adapter.into_init_info(spec.authority.map(Into::into)))
+        .collect())
+}
+
+/// Mirror of mpl-core's `PluginType`, used to pick the plugin to remove.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PluginTypeArgs {
+    Royalties,
+    FreezeDelegate,
+    BurnDelegate,
+    TransferDelegate,
+    UpdateDelegate,
+    PermanentFreezeDelegate,
+    Attributes,
+    PermanentTransferDelegate,
+    PermanentBurnDelegate,
+    Edition,
+    MasterEdition,
+    AddBlocker,
+    ImmutableMetadata,
+    VerifiedCreators,
+    Autograph,
+    BubblegumV2,
+    FreezeExecute,
+}
+
+impl PluginTypeArgs {
+    /// Same as [`PluginArgs::is_owner_managed`].
+    pub fn is_owner_managed(&self) -> bool {
+        matches!(
+            self,
+            PluginTypeArgs::FreezeDelegate
+                | PluginTypeArgs::BurnDelegate
+                | PluginTypeArgs::TransferDelegate
+        )
+    }
+}
+
+impl From<PluginTypeArgs> for PluginType {
+    fn from(plugin_type: PluginTypeArgs) -> Self {
+        match plugin_type {
+            PluginTypeArgs::Royalties => PluginType::Royalties,
+            PluginTypeArgs::FreezeDelegate => PluginType::FreezeDelegate,
+            PluginTypeArgs::BurnDelegate => PluginType::BurnDelegate,
+            PluginTypeArgs::TransferDelegate => PluginType::TransferDelegate,
+            PluginTypeArgs::UpdateDelegate => PluginType::UpdateDelegate,
+            PluginTypeArgs::PermanentFreezeDelegate => PluginType::PermanentFreezeDelegate,
+            PluginTypeArgs::Attributes => PluginType::Attributes,
+            PluginTypeArgs::PermanentTransferDelegate => PluginType::PermanentTransferDelegate,
+            PluginTypeArgs::PermanentBurnDelegate => PluginType::PermanentBurnDelegate,
+            PluginTypeArgs::Edition => PluginType::Edition,
+            PluginTypeArgs::MasterEdition => PluginType::MasterEdition,
+            PluginTypeArgs::AddBlocker => PluginType::AddBlocker,
+            PluginTypeArgs::ImmutableMetadata => PluginType::ImmutableMetadata,
+            PluginTypeArgs::VerifiedCreators => PluginType::VerifiedCreators,
+            PluginTypeArgs::Autograph => PluginType::Autograph,
+            PluginTypeArgs::BubblegumV2 => PluginType::BubblegumV2,
+            PluginTypeArgs::FreezeExecute => PluginType::FreezeExecute,
+        }
+    }
+}
+
+/// Mirror of mpl-core's `ExternalPluginAdapterKey`, used to pick the adapter to remove.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ExternalPluginAdapterKeyArgs {
+    LifecycleHook { hooked_program: Pubkey },
+    Oracle { base_address: Pubkey },
+    AppData { data_authority: PluginAuthorityArgs },
+    LinkedLifecycleHook { hooked_program: Pubkey },
+    LinkedAppData { data_authority: PluginAuthorityArgs },
+}
+
+impl From<ExternalPluginAdapterKeyArgs> for ExternalPluginAdapterKey {
+    fn from(key: ExternalPluginAdapterKeyArgs) -> Self {
+        match key {
+            ExternalPluginAdapterKeyArgs::LifecycleHook { hooked_program } => {
+                ExternalPluginAdapterKey::LifecycleHook(hooked_program)
+            }
+            ExternalPluginAdapterKeyArgs::Oracle { base_address } => {
+                ExternalPluginAdapterKey::Oracle(base_address)
+            }
+            ExternalPluginAdapterKeyArgs::AppData { data_authority } => {
+                ExternalPluginAdapterKey::AppData(data_authority.into())
+            }
+            ExternalPluginAdapterKeyArgs::LinkedLifecycleHook { hooked_program } => {
+                ExternalPluginAdapterKey::LinkedLifecycleHook(hooked_program)
+            }
+            ExternalPluginAdapterKeyArgs::LinkedAppData { data_authority } => {
+                ExternalPluginAdapterKey::LinkedAppData(data_authority.into())
+            }
+        }
+    }
+}
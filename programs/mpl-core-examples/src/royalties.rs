@@ -0,0 +1,308 @@
+use anchor_lang::prelude::*;
+use mpl_core::{
+    fetch_asset_plugin, fetch_collection_plugin,
+    types::{Creator, PluginType, Royalties, RuleSet},
+};
+
+use crate::{error::ErrorCode, CreatorArgs, MAX_CREATORS, MAX_RULE_SET_PROGRAMS};
+
+/// Which programs are allowed to move an asset with royalties.
+/// Mirror of mpl-core's `RuleSet` that can be passed as an instruction argument.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub enum RuleSetArgs {
+    /// Any program can transfer the asset.
+    #[default]
+    None,
+    /// Only the listed programs can transfer the asset.
+    ProgramAllowList(Vec<Pubkey>),
+    /// Every program except the listed ones can transfer the asset.
+    ProgramDenyList(Vec<Pubkey>),
+}
+
+impl From<RuleSetArgs> for RuleSet {
+    fn from(rule_set: RuleSetArgs) -> Self {
+        match rule_set {
+            RuleSetArgs::None => RuleSet::None,
+            RuleSetArgs::ProgramAllowList(programs) => RuleSet::ProgramAllowList(programs),
+            RuleSetArgs::ProgramDenyList(programs) => RuleSet::ProgramDenyList(programs),
+        }
+    }
+}
+
+/// Checks royalty arguments before they are sent to mpl-core.
+/// Shared by the asset and collection royalty instructions so both paths enforce the same rules:
+/// - basis points can not exceed 10000 (100%)
+/// - there is at least one and at most `MAX_CREATORS` creators
+/// - a creator address can only appear once
+/// - creator percentages add up to exactly 100
+pub fn validate_royalties(basis_points: u16, creators: &[CreatorArgs]) -> Result<()> {
+    require!(basis_points <= 10_000, ErrorCode::InvalidBasisPoints);
+    require!(!creators.is_empty(), ErrorCode::InvalidCreatorShares);
+    require!(creators.len() <= MAX_CREATORS, ErrorCode::TooManyCreators);
+
+    for (index, creator) in creators.iter().enumerate() {
+        require!(
+            !creators[..index]
+                .iter()
+                .any(|previous| previous.address == creator.address),
+            ErrorCode::DuplicateCreator
+        );
+    }
+
+    let total: u16 = creators.iter().map(|c| c.percentage as u16).sum();
+    require!(total == 100, ErrorCode::InvalidCreatorShares);
+    Ok(())
+}
+
+/// Checks that an allow/deny list is non-empty, bounded and has no duplicate programs.
+/// An empty allow list would lock the asset for every program, so it is rejected.
+pub fn validate_rule_set(rule_set: &RuleSetArgs) -> Result<()> {
+    let programs = match rule_set {
+        RuleSetArgs::None => return Ok(()),
+        RuleSetArgs::ProgramAllowList(programs) | RuleSetArgs::ProgramDenyList(programs) => {
+            programs
+        }
+    };
+
+    require!(!programs.is_empty(), ErrorCode::EmptyRuleSet);
+    require!(
+        programs.len() <= MAX_RULE_SET_PROGRAMS,
+        ErrorCode::TooManyRuleSetPrograms
+    );
+    for (index, program) in programs.iter().enumerate() {
+        require!(
+            !programs[..index].contains(program),
+            ErrorCode::DuplicateRuleSetProgram
+        );
+    }
+    Ok(())
+}
+
+/// Maps the incoming creators into the expected `Creator` format.
+pub fn to_creators(creators: Vec<CreatorArgs>) -> Vec<Creator> {
+    creators
+        .into_iter()
+        .map(|c| Creator {
+            address: c.address,
+            percentage: c.percentage,
+        })
+        .collect()
+}
+
+/// Royalties of an asset, falling back to the plugin of its collection.
+pub fn load_royalties(asset: &AccountInfo, collection: Option<&AccountInfo>) -> Option<Royalties> {
+    fetch_asset_plugin::<Royalties>(asset, PluginType::Royalties)
+        .or_else(|err| match collection {
+            Some(collection) => fetch_collection_plugin(collection, PluginType::Royalties),
+            None => Err(err),
+        })
+        .ok()
+        .map(|(_, royalties, _)| royalties)
+}
+
+/// What each creator is owed when an asset sells for `price`, in the order of the creators.
+/// The royalty is rounded down once, on the total. It is then split by percentage with the
+/// largest remainder method, so the shares add up to it exactly and no creator loses more
+/// than a lamport (or base unit) to rounding.
+pub fn royalty_payouts(price: u64, royalties: &Royalties) -> Vec<(Pubkey, u64)> {
+    let total = price as u128 * royalties.basis_points.min(10_000) as u128 / 10_000;
+    let shares: Vec<(u128, u128)> = royalties
+        .creators
+        .iter()
+        .map(|creator| {
+            let exact = total * creator.percentage as u128;
+            (exact / 100, exact % 100)
+        })
+        .collect();
+
+    // The dust left by rounding every share down goes one unit at a time to the
+    // largest remainders, the first creators winning ties
+    let mut dust = total - shares.iter().map(|(share, _)| share).sum::<u128>();
+    let mut order: Vec<usize> = (0..shares.len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(shares[index].1));
+    let mut amounts: Vec<u128> = shares.iter().map(|(share, _)| *share).collect();
+    for index in order {
+        if dust == 0 || shares[index].1 == 0 {
+            break;
+        }
+        amounts[index] += 1;
+        dust -= 1;
+    }
+
+    royalties
+        .creators
+        .iter()
+        .zip(amounts)
+        .map(|(creator, amount)| (creator.address, amount as u64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn creator(percentage: u8) -> CreatorArgs {
+        CreatorArgs {
+            address: Pubkey::new_unique(),
+            percentage,
+        }
+    }
+
+    #[test]
+    fn accepts_valid_royalties() {
+        assert!(validate_royalties(500, &[creator(100)]).is_ok());
+        assert!(validate_royalties(10_000, &[creator(60), creator(40)]).is_ok());
+        assert!(validate_royalties(0, &[creator(100)]).is_ok());
+    }
+
+    #[test]
+    fn rejects_basis_points_above_10000() {
+        assert_eq!(
+            validate_royalties(10_001, &[creator(100)]).unwrap_err(),
+            ErrorCode::InvalidBasisPoints.into()
+        );
+    }
+
+    #[test]
+    fn rejects_shares_not_adding_up_to_100() {
+        assert_eq!(
+            validate_royalties(500, &[creator(50), creator(49)]).unwrap_err(),
+            ErrorCode::InvalidCreatorShares.into()
+        );
+        assert_eq!(
+            validate_royalties(500, &[creator(100), creator(1)]).unwrap_err(),
+            ErrorCode::InvalidCreatorShares.into()
+        );
+        assert_eq!(
+            validate_royalties(500, &[]).unwrap_err(),
+            ErrorCode::InvalidCreatorShares.into()
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_creators() {
+        let first = creator(50);
+        let duplicate = CreatorArgs {
+            address: first.address,
+            percentage: 50,
+        };
+        assert_eq!(
+            validate_royalties(500, &[first, duplicate]).unwrap_err(),
+            ErrorCode::DuplicateCreator.into()
+        );
+    }
+
+    #[test]
+    fn rejects_too_many_creators() {
+        let creators: Vec<CreatorArgs> = (0..MAX_CREATORS + 1).map(|_| creator(0)).collect();
+        assert_eq!(
+            validate_royalties(500, &creators).unwrap_err(),
+            ErrorCode::TooManyCreators.into()
+        );
+    }
+
+    #[test]
+    fn accepts_valid_rule_sets() {
+        assert!(validate_rule_set(&RuleSetArgs::None).is_ok());
+        assert!(
+            validate_rule_set(&RuleSetArgs::ProgramAllowList(vec![Pubkey::new_unique()])).is_ok()
+        );
+        assert!(validate_rule_set(&RuleSetArgs::ProgramDenyList(vec![
+            Pubkey::new_unique(),
+            Pubkey::new_unique()
+        ]))
+        .is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_rule_sets() {
+        assert_eq!(
+            validate_rule_set(&RuleSetArgs::ProgramAllowList(vec![])).unwrap_err(),
+            ErrorCode::EmptyRuleSet.into()
+        );
+
+        let program = Pubkey::new_unique();
+        assert_eq!(
+            validate_rule_set(&RuleSetArgs::ProgramDenyList(vec![program, program])).unwrap_err(),
+            ErrorCode::DuplicateRuleSetProgram.into()
+        );
+
+        let programs = (0..MAX_RULE_SET_PROGRAMS + 1)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        assert_eq!(
+            validate_rule_set(&RuleSetArgs::ProgramAllowList(programs)).unwrap_err(),
+            ErrorCode::TooManyRuleSetPrograms.into()
+        );
+    }
+
+    #[test]
+    fn maps_creators() {
+        let args = creator(100);
+        let address = args.address;
+        let creators = to_creators(vec![args]);
+        assert_eq!(creators.len(), 1);
+        assert_eq!(creators[0].address, address);
+        assert_eq!(creators[0].percentage, 100);
+    }
+
+    fn royalties(basis_points: u16, percentages: &[u8]) -> Royalties {
+        Royalties {
+            basis_points,
+            creators: percentages
+                .iter()
+                .map(|&percentage| Creator {
+                    address: Pubkey::new_unique(),
+                    percentage,
+                })
+                .collect(),
+            rule_set: RuleSet::None,
+        }
+    }
+
+    #[test]
+    fn splits_royalties_by_creator_percentage() {
+        let royalties = royalties(500, &[70, 30]);
+        let payouts = royalty_payouts(1_000_000, &royalties);
+
+        assert_eq!(payouts[0], (royalties.creators[0].address, 35_000));
+        assert_eq!(payouts[1], (royalties.creators[1].address, 15_000));
+    }
+
+    #[test]
+    fn gives_the_dust_to_the_largest_remainders() {
+        // 5% of 999 rounds down to 49, split 16.66 / 16.17 / 16.17
+        let payouts = royalty_payouts(999, &royalties(500, &[34, 33, 33]));
+        let amounts: Vec<u64> = payouts.iter().map(|(_, amount)| *amount).collect();
+
+        assert_eq!(amounts, vec![17, 16, 16]);
+    }
+
+    #[test]
+    fn shares_add_up_to_the_royalty() {
+        let royalties = royalties(777, &[1, 13, 29, 57]);
+        for price in [0, 1, 99, 1_001, 123_457, 10_000_000_001] {
+            let total: u64 = royalty_payouts(price, &royalties)
+                .iter()
+                .map(|(_, amount)| amount)
+                .sum();
+            assert_eq!(total as u128, price as u128 * 777 / 10_000, "price {price}");
+        }
+    }
+
+    #[test]
+    fn pays_nothing_to_a_creator_without_a_share() {
+        let payouts = royalty_payouts(1_001, &royalties(1_000, &[0, 50, 50]));
+        let amounts: Vec<u64> = payouts.iter().map(|(_, amount)| *amount).collect();
+
+        assert_eq!(amounts, vec![0, 50, 50]);
+    }
+
+    #[test]
+    fn never_pays_more_than_the_price() {
+        let payouts = royalty_payouts(u64::MAX, &royalties(10_000, &[50, 50]));
+        let total: u128 = payouts.iter().map(|(_, amount)| *amount as u128).sum();
+
+        assert!(total <= u64::MAX as u128);
+    }
+}
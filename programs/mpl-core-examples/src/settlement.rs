@@ -0,0 +1,137 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::{
+    error::ErrorCode,
+    royalties::{load_royalties, royalty_payouts},
+};
+
+/// What a sale is paid with.
+pub enum Payment<'a, 'info> {
+    /// Lamports from a wallet.
+    Lamports {
+        payer: &'a AccountInfo<'info>,
+        system_program: &'a AccountInfo<'info>,
+    },
+    /// Lamports held by an account of this program, like the escrow of an offer.
+    Escrow { account: &'a AccountInfo<'info> },
+    /// SPL or Token-2022 tokens from a token account of `authority`.
+    Tokens {
+        authority: &'a AccountInfo<'info>,
+        from: &'a InterfaceAccount<'info, TokenAccount>,
+        mint: &'a InterfaceAccount<'info, Mint>,
+        token_program: &'a Interface<'info, TokenInterface>,
+    },
+}
+
+impl<'a, 'info> Payment<'a, 'info> {
+    /// Sends `amount` to `to`, which must be a token account of the mint when paying in tokens.
+    pub fn pay(&self, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        match self {
+            Payment::Lamports {
+                payer,
+                system_program,
+            } => transfer(
+                CpiContext::new(
+                    (*system_program).clone(),
+                    Transfer {
+                        from: (*payer).clone(),
+                        to: to.clone(),
+                    },
+                ),
+                amount,
+            ),
+            Payment::Escrow { account } => {
+                **account.try_borrow_mut_lamports()? = account
+                    .lamports()
+                    .checked_sub(amount)
+                    .ok_or(ProgramError::InsufficientFunds)?;
+                **to.try_borrow_mut_lamports()? = to
+                    .lamports()
+                    .checked_add(amount)
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+                Ok(())
+            }
+            Payment::Tokens {
+                authority,
+                from,
+                mint,
+                token_program,
+            } => transfer_checked(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        from: from.to_account_info(),
+                        mint: mint.to_account_info(),
+                        to: to.clone(),
+                        authority: (*authority).clone(),
+                    },
+                ),
+                amount,
+                mint.decimals,
+            ),
+        }
+    }
+
+    /// Checks that `account` can receive the payouts of `creator`: the wallet itself for
+    /// lamports, a token account of the mint owned by the creator for tokens.
+    fn validate_recipient(&self, account: &AccountInfo, creator: &Pubkey) -> Result<()> {
+        match self {
+            Payment::Lamports { .. } | Payment::Escrow { .. } => {
+                require_keys_eq!(*account.key, *creator, ErrorCode::CreatorAccountMismatch);
+            }
+            Payment::Tokens {
+                mint,
+                token_program,
+                ..
+            } => {
+                require_keys_eq!(
+                    *account.owner,
+                    token_program.key(),
+                    ErrorCode::CreatorAccountMismatch
+                );
+                let token_account = TokenAccount::try_deserialize(&mut &account.data.borrow()[..])?;
+                require!(
+                    token_account.owner == *creator && token_account.mint == mint.key(),
+                    ErrorCode::CreatorAccountMismatch
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Pays the creators of an asset their royalties on a sale for `price`, reading the `Royalties`
+/// plugin of the asset or else of its collection. `creators` are the accounts receiving the
+/// payouts, in the order of the plugin. Returns the total paid, which the seller doesn't get.
+pub fn settle_royalties<'info>(
+    asset: &AccountInfo<'info>,
+    collection: Option<&AccountInfo<'info>>,
+    price: u64,
+    payment: &Payment<'_, 'info>,
+    creators: &[AccountInfo<'info>],
+) -> Result<u64> {
+    let payouts = load_royalties(asset, collection)
+        .map(|royalties| royalty_payouts(price, &royalties))
+        .unwrap_or_default();
+    require!(
+        creators.len() == payouts.len(),
+        ErrorCode::CreatorAccountMismatch
+    );
+
+    let mut total = 0;
+    for ((creator, amount), account) in payouts.into_iter().zip(creators) {
+        payment.validate_recipient(account, &creator)?;
+        payment.pay(account, amount)?;
+        total += amount;
+    }
+    Ok(total)
+}
@@ -1,8 +1,633 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{
+    prelude::*,
+    system_program::{
+        allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+    },
+};
+
+use crate::{
+    error::ErrorCode, BASE_REWARD_MULTIPLIER, COLLECTION_AUTHORITY_SEED, KEY_ASSET_RECORD_SEED,
+    LISTING_SEED, MAX_ATTRIBUTE_LENGTH, MAX_MINT_PHASES, MAX_NAME_LENGTH, MAX_REWARD_MULTIPLIERS,
+    MAX_URI_LENGTH, MINT_NUMBER_PLACEHOLDER, MINT_RECORD_SEED, OFFER_SEED, PENDING_REWARDS_SEED,
+    STAKE_POOL_SEED,
+};
 
 #[account]
 #[derive(InitSpace)]
 pub struct EditionCouter{
     pub edition_count:u32,
     
-}
\ No newline at end of file
+}
+
+/// A pending handover of a collection's update authority.
+/// While it exists the PDA itself is the update authority of the collection,
+/// so neither side can be locked out: the new authority accepts, or the proposer cancels.
+#[account]
+#[derive(InitSpace)]
+pub struct UpdateAuthorityProposal {
+    pub collection: Pubkey,
+    pub proposer: Pubkey,
+    pub new_authority: Pubkey,
+    pub bump: u8,
+}
+
+/// Makes this program the update authority of a collection.
+/// The update authority is the PDA derived from this account with [`COLLECTION_AUTHORITY_SEED`],
+/// so only instructions of this program can sign for the collection.
+#[account]
+#[derive(InitSpace)]
+pub struct CollectionConfig {
+    pub collection: Pubkey,
+    /// Who can configure the collection and mint from it directly.
+    pub authority: Pubkey,
+    /// How many assets a wallet can receive in the current phase, across every mint instruction.
+    pub max_per_wallet: Option<u32>,
+    /// Starting a new phase resets the mint count of every wallet.
+    pub phase: u32,
+    /// Root of the allowlist, see [`crate::merkle::AllowlistProof`]. Only listed wallets can
+    /// receive assets while it is set, up to their allocation per phase.
+    pub allowlist_root: Option<[u8; 32]>,
+    pub collection_authority_bump: u8,
+    pub bump: u8,
+}
+
+impl CollectionConfig {
+    /// Seeds of the collection authority PDA of the config at `config`, used with `invoke_signed`.
+    pub fn collection_authority_seeds<'a>(config: &'a Pubkey, bump: &'a [u8; 1]) -> [&'a [u8]; 3] {
+        [COLLECTION_AUTHORITY_SEED.as_bytes(), config.as_ref(), bump]
+    }
+
+    /// Checks that the config authority signed.
+    pub fn validate_authority(&self, authority: Option<&Pubkey>) -> Result<()> {
+        let authority = authority.ok_or(ErrorCode::UnauthorizedSigner)?;
+        require_keys_eq!(*authority, self.authority, ErrorCode::UnauthorizedSigner);
+        Ok(())
+    }
+
+    /// Checks that `collection_authority` is the collection authority PDA of the config at `config`.
+    pub fn validate_collection_authority(
+        &self,
+        config: &Pubkey,
+        collection_authority: &Pubkey,
+    ) -> Result<()> {
+        let bump = [self.collection_authority_bump];
+        let expected = Pubkey::create_program_address(
+            &Self::collection_authority_seeds(config, &bump),
+            &crate::ID,
+        )
+        .map_err(|_| error!(ErrorCode::InvalidCollectionAuthority))?;
+        require_keys_eq!(
+            *collection_authority,
+            expected,
+            ErrorCode::InvalidCollectionAuthority
+        );
+        Ok(())
+    }
+}
+
+/// Who can mint in a phase of a mint config.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
+pub enum MintRule {
+    /// Any wallet.
+    Public,
+    /// Wallets on the allowlist with this root, see [`crate::merkle::AllowlistProof`].
+    Allowlist { root: [u8; 32] },
+    /// Wallets holding an asset of another Core collection. With `single_use`,
+    /// each asset of that collection can only be used for one mint.
+    Holders {
+        collection: Pubkey,
+        single_use: bool,
+    },
+}
+
+/// A window of a mint schedule with its own price, limit and rule.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
+pub struct MintPhase {
+    /// Unix timestamp the phase opens at.
+    pub start_time: i64,
+    /// Unix timestamp the phase closes at. Only the last phase can stay open forever.
+    pub end_time: Option<i64>,
+    /// Price of a mint in lamports.
+    pub price: u64,
+    /// How many assets a wallet can mint in this phase.
+    pub max_per_wallet: Option<u32>,
+    pub rule: MintRule,
+}
+
+impl MintPhase {
+    pub fn has_started(&self, now: i64) -> bool {
+        now >= self.start_time
+    }
+
+    fn is_active(&self, now: i64) -> bool {
+        self.has_started(now) && !matches!(self.end_time, Some(end_time) if now >= end_time)
+    }
+}
+
+/// Public mint schedule of a program-controlled collection, see `mint_from_config`.
+#[account]
+#[derive(InitSpace)]
+pub struct MintConfig {
+    pub collection: Pubkey,
+    /// Receives the price of every mint.
+    pub treasury: Pubkey,
+    pub max_supply: u32,
+    pub minted: u32,
+    /// Name of the minted assets, `{id}` is replaced by the mint number.
+    #[max_len(MAX_NAME_LENGTH)]
+    pub name_template: String,
+    /// URI of the minted assets, `{id}` is replaced by the mint number.
+    #[max_len(MAX_URI_LENGTH)]
+    pub uri_template: String,
+    /// Phases of the mint, in order.
+    #[max_len(MAX_MINT_PHASES)]
+    pub phases: Vec<MintPhase>,
+    /// The phase the last mint happened in. Moving to another one starts a new
+    /// phase of the collection config, which resets the mint count of every wallet.
+    pub current_phase: Option<u8>,
+    pub bump: u8,
+}
+
+impl MintConfig {
+    /// Checks that the phases are ordered and don't overlap.
+    pub fn validate_phases(phases: &[MintPhase]) -> Result<()> {
+        require!(!phases.is_empty(), ErrorCode::EmptyMintSchedule);
+        require!(
+            phases.len() <= MAX_MINT_PHASES,
+            ErrorCode::TooManyMintPhases
+        );
+        for phase in phases {
+            if let Some(end_time) = phase.end_time {
+                require!(end_time > phase.start_time, ErrorCode::InvalidMintWindow);
+            }
+        }
+        for pair in phases.windows(2) {
+            let end_time = pair[0].end_time.ok_or(ErrorCode::OverlappingMintPhases)?;
+            require!(
+                end_time <= pair[1].start_time,
+                ErrorCode::OverlappingMintPhases
+            );
+        }
+        Ok(())
+    }
+
+    /// The phase open at `now` and its index.
+    pub fn active_phase(&self, now: i64) -> Result<(u8, &MintPhase)> {
+        if let Some(index) = self.phases.iter().position(|phase| phase.is_active(now)) {
+            return Ok((index as u8, &self.phases[index]));
+        }
+        if self.phases.iter().any(|phase| !phase.has_started(now)) {
+            return err!(ErrorCode::MintNotStarted);
+        }
+        err!(ErrorCode::MintEnded)
+    }
+
+    /// Name and URI of the asset with the given mint number.
+    pub fn asset_metadata(&self, number: u32) -> (String, String) {
+        (
+            render_template(&self.name_template, number),
+            render_template(&self.uri_template, number),
+        )
+    }
+}
+
+/// Replaces the mint number placeholder of a name or URI template.
+pub fn render_template(template: &str, number: u32) -> String {
+    template.replace(MINT_NUMBER_PLACEHOLDER, &number.to_string())
+}
+
+/// How many assets a wallet minted from a collection in a phase.
+#[account]
+#[derive(InitSpace)]
+pub struct MintRecord {
+    pub collection: Pubkey,
+    pub minter: Pubkey,
+    pub minted: u32,
+    /// The phase `minted` counts for, an older phase means nothing was minted in this one.
+    pub phase: u32,
+    pub bump: u8,
+}
+
+impl MintRecord {
+    /// Counts one more mint in `phase`, rejecting it once `max_per_wallet` is reached.
+    pub fn record_mint(&mut self, phase: u32, max_per_wallet: Option<u32>) -> Result<()> {
+        if self.phase != phase {
+            self.phase = phase;
+            self.minted = 0;
+        }
+        if let Some(max_per_wallet) = max_per_wallet {
+            require!(
+                self.minted < max_per_wallet,
+                ErrorCode::WalletMintLimitReached
+            );
+        }
+        self.minted += 1;
+        Ok(())
+    }
+
+    /// Counts a mint of `minter` into the collection of `config`, creating the record on its first mint.
+    /// Used by the instructions where the minter is only known at runtime, so the record
+    /// can't be an `init_if_needed` account. `allocation` is the allowlist allocation of the minter.
+    pub fn track<'info>(
+        record: &AccountInfo<'info>,
+        config: &CollectionConfig,
+        minter: &Pubkey,
+        allocation: Option<u32>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> Result<()> {
+        let (expected, bump) = Pubkey::find_program_address(
+            &[
+                MINT_RECORD_SEED.as_bytes(),
+                config.collection.as_ref(),
+                minter.as_ref(),
+            ],
+            &crate::ID,
+        );
+        require_keys_eq!(*record.key, expected, ErrorCode::InvalidMintRecord);
+
+        let mut mint_record = if record.owner == &crate::ID {
+            MintRecord::try_deserialize(&mut &record.try_borrow_data()?[..])?
+        } else {
+            create_program_account(
+                record,
+                8 + MintRecord::INIT_SPACE,
+                &[
+                    MINT_RECORD_SEED.as_bytes(),
+                    config.collection.as_ref(),
+                    minter.as_ref(),
+                    &[bump],
+                ],
+                payer,
+                system_program,
+            )?;
+            MintRecord {
+                collection: config.collection,
+                minter: *minter,
+                minted: 0,
+                phase: config.phase,
+                bump,
+            }
+        };
+
+        let max_per_wallet = config.max_per_wallet.into_iter().chain(allocation).min();
+        mint_record.record_mint(config.phase, max_per_wallet)?;
+        mint_record.try_serialize(&mut &mut record.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+}
+
+/// Marks a key asset of a holders phase as used, so it can only mint once.
+#[account]
+#[derive(InitSpace)]
+pub struct KeyAssetRecord {
+    /// The collection the key asset was used to mint from.
+    pub collection: Pubkey,
+    pub key_asset: Pubkey,
+    pub bump: u8,
+}
+
+impl KeyAssetRecord {
+    /// Creates the record of `key_asset` for the collection of `config`, failing if it exists.
+    pub fn mark_used<'info>(
+        record: &AccountInfo<'info>,
+        config: &CollectionConfig,
+        key_asset: &Pubkey,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> Result<()> {
+        let (expected, bump) = Pubkey::find_program_address(
+            &[
+                KEY_ASSET_RECORD_SEED.as_bytes(),
+                config.collection.as_ref(),
+                key_asset.as_ref(),
+            ],
+            &crate::ID,
+        );
+        require_keys_eq!(*record.key, expected, ErrorCode::InvalidKeyAssetRecord);
+        require_keys_neq!(*record.owner, crate::ID, ErrorCode::KeyAssetAlreadyUsed);
+
+        create_program_account(
+            record,
+            8 + KeyAssetRecord::INIT_SPACE,
+            &[
+                KEY_ASSET_RECORD_SEED.as_bytes(),
+                config.collection.as_ref(),
+                key_asset.as_ref(),
+                &[bump],
+            ],
+            payer,
+            system_program,
+        )?;
+        KeyAssetRecord {
+            collection: config.collection,
+            key_asset: *key_asset,
+            bump,
+        }
+        .try_serialize(&mut &mut record.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+}
+
+/// Staking pool of a collection. Rewards accrue at `reward_rate` per staked asset and second,
+/// weighted by the multiplier of each asset.
+#[account]
+#[derive(InitSpace)]
+pub struct StakePool {
+    pub collection: Pubkey,
+    /// Who can configure the pool.
+    pub authority: Pubkey,
+    pub reward_rate: u64,
+    /// Rewards earned by an asset staked since the pool was created, up to `last_update`.
+    pub reward_per_asset: u128,
+    pub last_update: i64,
+    pub total_staked: u32,
+    pub total_rewards_claimed: u64,
+    /// Token paid out from the reward vault. Without it rewards are only counted.
+    pub reward_mint: Option<Pubkey>,
+    /// Key of the `Attributes` entry whose value selects the multiplier of an asset.
+    #[max_len(MAX_ATTRIBUTE_LENGTH)]
+    pub multiplier_attribute: Option<String>,
+    #[max_len(MAX_REWARD_MULTIPLIERS)]
+    pub multipliers: Vec<RewardMultiplier>,
+    pub bump: u8,
+}
+
+/// Multiplier of the assets whose multiplier attribute has the given value.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RewardMultiplier {
+    #[max_len(MAX_ATTRIBUTE_LENGTH)]
+    pub value: String,
+    /// In basis points, 10000 is 1x.
+    pub multiplier_bps: u16,
+}
+
+impl StakePool {
+    /// Seeds of the pool of `collection`, used with `invoke_signed`.
+    pub fn signer_seeds<'a>(collection: &'a Pubkey, bump: &'a [u8; 1]) -> [&'a [u8]; 3] {
+        [STAKE_POOL_SEED.as_bytes(), collection.as_ref(), bump]
+    }
+
+    /// Brings `reward_per_asset` up to `now`. Must run before the rate or a stake changes.
+    pub fn accrue(&mut self, now: i64) -> Result<()> {
+        let elapsed = now.saturating_sub(self.last_update).max(0) as u128;
+        self.reward_per_asset = self
+            .reward_per_asset
+            .checked_add(elapsed * self.reward_rate as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.last_update = now;
+        Ok(())
+    }
+
+    /// Multiplier of an asset with the given attribute value.
+    pub fn multiplier_for(&self, value: Option<&str>) -> u16 {
+        value
+            .and_then(|value| {
+                self.multipliers
+                    .iter()
+                    .find(|multiplier| multiplier.value == value)
+            })
+            .map_or(BASE_REWARD_MULTIPLIER, |multiplier| {
+                multiplier.multiplier_bps
+            })
+    }
+
+    /// Adds the rewards `stake` earned since its last claim, weighted by its multiplier,
+    /// to its unpaid rewards and returns them. The pool must be accrued first.
+    pub fn settle(&mut self, stake: &mut StakeRecord) -> Result<u64> {
+        let earned = (self.reward_per_asset - stake.reward_debt)
+            .checked_mul(stake.multiplier_bps as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / BASE_REWARD_MULTIPLIER as u128;
+        stake.reward_debt = self.reward_per_asset;
+        stake.unpaid_rewards = u64::try_from(earned)
+            .ok()
+            .and_then(|earned| stake.unpaid_rewards.checked_add(earned))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(stake.unpaid_rewards)
+    }
+
+    /// Records that `amount` of the unpaid rewards of `stake` were paid out.
+    pub fn record_payout(&mut self, stake: &mut StakeRecord, amount: u64) -> Result<()> {
+        stake.unpaid_rewards -= amount;
+        stake.rewards_claimed = stake
+            .rewards_claimed
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.record_claim(amount)
+    }
+
+    /// Adds `amount` to the rewards paid out by the pool.
+    pub fn record_claim(&mut self, amount: u64) -> Result<()> {
+        self.total_rewards_claimed = self
+            .total_rewards_claimed
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// An asset staked in a pool. It stays with its owner, frozen by the pool PDA.
+#[account]
+#[derive(InitSpace)]
+pub struct StakeRecord {
+    pub pool: Pubkey,
+    pub asset: Pubkey,
+    pub owner: Pubkey,
+    pub start_time: i64,
+    /// `reward_per_asset` of the pool when the rewards of the asset were last settled.
+    pub reward_debt: u128,
+    /// Multiplier of the asset when it was staked, in basis points.
+    pub multiplier_bps: u16,
+    /// Settled rewards the reward vault could not pay yet.
+    pub unpaid_rewards: u64,
+    pub rewards_claimed: u64,
+    pub bump: u8,
+}
+
+/// Rewards the reward vault could not pay when the owner unstaked, across all their assets.
+/// They stay claimable with `claim_pending_rewards` once the vault is funded.
+#[account]
+#[derive(InitSpace)]
+pub struct PendingRewards {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl PendingRewards {
+    /// Adds `amount` to the pending rewards of `owner` in `pool`, creating the account on first use.
+    /// Only created when something is owed, so unstaking from a funded pool costs no rent.
+    pub fn add<'info>(
+        record: &AccountInfo<'info>,
+        pool: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> Result<()> {
+        let (expected, bump) = Pubkey::find_program_address(
+            &[
+                PENDING_REWARDS_SEED.as_bytes(),
+                pool.as_ref(),
+                owner.as_ref(),
+            ],
+            &crate::ID,
+        );
+        require_keys_eq!(*record.key, expected, ErrorCode::InvalidPendingRewards);
+
+        let mut pending_rewards = if record.owner == &crate::ID {
+            PendingRewards::try_deserialize(&mut &record.try_borrow_data()?[..])?
+        } else {
+            create_program_account(
+                record,
+                8 + PendingRewards::INIT_SPACE,
+                &[
+                    PENDING_REWARDS_SEED.as_bytes(),
+                    pool.as_ref(),
+                    owner.as_ref(),
+                    &[bump],
+                ],
+                payer,
+                system_program,
+            )?;
+            PendingRewards {
+                pool: *pool,
+                owner: *owner,
+                amount: 0,
+                bump,
+            }
+        };
+
+        pending_rewards.amount = pending_rewards
+            .amount
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        pending_rewards.try_serialize(&mut &mut record.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+}
+
+/// An asset for sale. It stays with the seller, frozen by the listing PDA,
+/// which can transfer it to the buyer.
+#[account]
+#[derive(InitSpace)]
+pub struct Listing {
+    pub asset: Pubkey,
+    pub seller: Pubkey,
+    /// In lamports, royalties included.
+    pub price: u64,
+    pub expiry: Option<i64>,
+    pub bump: u8,
+}
+
+impl Listing {
+    /// Seeds of the listing of `asset`, used with `invoke_signed`.
+    pub fn signer_seeds<'a>(asset: &'a Pubkey, bump: &'a [u8; 1]) -> [&'a [u8]; 3] {
+        [LISTING_SEED.as_bytes(), asset.as_ref(), bump]
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        matches!(self.expiry, Some(expiry) if now >= expiry)
+    }
+}
+
+/// A bid on any asset of a collection. The PDA escrows `price` lamports for each asset
+/// still wanted, on top of its rent.
+#[account]
+#[derive(InitSpace)]
+pub struct Offer {
+    pub collection: Pubkey,
+    pub buyer: Pubkey,
+    /// In lamports per asset, royalties included.
+    pub price: u64,
+    /// How many more assets the buyer wants.
+    pub quantity: u32,
+    pub expiry: Option<i64>,
+    pub bump: u8,
+}
+
+impl Offer {
+    /// Seeds of the offer of `buyer` on `collection`, used with `invoke_signed`.
+    pub fn signer_seeds<'a>(
+        collection: &'a Pubkey,
+        buyer: &'a Pubkey,
+        bump: &'a [u8; 1],
+    ) -> [&'a [u8]; 4] {
+        [
+            OFFER_SEED.as_bytes(),
+            collection.as_ref(),
+            buyer.as_ref(),
+            bump,
+        ]
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        matches!(self.expiry, Some(expiry) if now >= expiry)
+    }
+}
+
+/// Creates a PDA of this program with `space` bytes, paid by `payer`.
+/// Lamports already sent to the address are kept, like Anchor's `init`,
+/// so nobody can block the account by funding it first.
+fn create_program_account<'info>(
+    account: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let lamports = account.lamports();
+    let signer = &[signer_seeds];
+
+    if lamports == 0 {
+        return create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+                signer,
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        );
+    }
+
+    if lamports < rent {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            rent - lamports,
+        )?;
+    }
+    allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate {
+                account_to_allocate: account.clone(),
+            },
+            signer,
+        ),
+        space as u64,
+    )?;
+    assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Assign {
+                account_to_assign: account.clone(),
+            },
+            signer,
+        ),
+        &crate::ID,
+    )
+}
@@ -0,0 +1,285 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::ProgramResult};
+use mpl_core::{
+    accounts::{BaseAssetV1, BaseCollectionV1},
+    fetch_asset_plugin, fetch_collection_plugin,
+    types::{Key, PluginAuthority, PluginType, UpdateAuthority, UpdateDelegate},
+};
+
+use crate::{
+    error::ErrorCode,
+    merkle::{allowlist_leaf, verify_proof, AllowlistProof},
+    state::CollectionConfig,
+    MAX_AUTOGRAPH_MESSAGE_LENGTH, MAX_NAME_LENGTH, MAX_URI_LENGTH, MPL_CORE_ID,
+};
+
+/// Checks the name and URI before they are sent to mpl-core,
+/// which would otherwise happily store (and charge rent for) anything.
+pub fn validate_metadata(name: &str, uri: &str) -> Result<()> {
+    require!(!name.is_empty(), ErrorCode::EmptyName);
+    require!(name.len() <= MAX_NAME_LENGTH, ErrorCode::NameTooLong);
+    require!(uri.len() <= MAX_URI_LENGTH, ErrorCode::UriTooLong);
+    Ok(())
+}
+
+/// Same as [`validate_metadata`] for the optional name and URI of plugins like `MasterEdition`.
+pub fn validate_optional_metadata(name: Option<&str>, uri: Option<&str>) -> Result<()> {
+    if let Some(name) = name {
+        require!(name.len() <= MAX_NAME_LENGTH, ErrorCode::NameTooLong);
+    }
+    if let Some(uri) = uri {
+        require!(uri.len() <= MAX_URI_LENGTH, ErrorCode::UriTooLong);
+    }
+    Ok(())
+}
+
+/// Autograph messages are stored on the asset forever, so keep them short.
+pub fn validate_autograph_message(message: &str) -> Result<()> {
+    require!(!message.is_empty(), ErrorCode::EmptyAutographMessage);
+    require!(
+        message.len() <= MAX_AUTOGRAPH_MESSAGE_LENGTH,
+        ErrorCode::AutographMessageTooLong
+    );
+    Ok(())
+}
+
+/// Deserializes the base of an mpl-core asset, making sure the account really is one.
+pub fn load_asset(asset: &AccountInfo) -> Result<BaseAssetV1> {
+    require_keys_eq!(*asset.owner, MPL_CORE_ID, ErrorCode::InvalidAsset);
+    let asset = BaseAssetV1::try_from(asset).map_err(|_| error!(ErrorCode::InvalidAsset))?;
+    require!(asset.key == Key::AssetV1, ErrorCode::InvalidAsset);
+    Ok(asset)
+}
+
+/// Deserializes the base of an mpl-core collection, making sure the account really is one.
+pub fn load_collection(collection: &AccountInfo) -> Result<BaseCollectionV1> {
+    require_keys_eq!(*collection.owner, MPL_CORE_ID, ErrorCode::InvalidCollection);
+    let collection =
+        BaseCollectionV1::try_from(collection).map_err(|_| error!(ErrorCode::InvalidCollection))?;
+    require!(
+        collection.key == Key::CollectionV1,
+        ErrorCode::InvalidCollection
+    );
+    Ok(collection)
+}
+
+/// Makes sure the collection account passed alongside an asset is the one the asset belongs to.
+/// mpl-core requires it for every lifecycle operation on an asset that is part of a collection.
+pub fn validate_asset_collection(
+    asset: &AccountInfo,
+    collection: Option<&AccountInfo>,
+) -> Result<BaseAssetV1> {
+    let asset = load_asset(asset)?;
+    match (&asset.update_authority, collection) {
+        (UpdateAuthority::Collection(expected), Some(collection)) => {
+            require_keys_eq!(*collection.key, *expected, ErrorCode::CollectionMismatch)
+        }
+        (UpdateAuthority::Collection(_), None) => return err!(ErrorCode::MissingCollection),
+        (_, Some(_)) => return err!(ErrorCode::CollectionMismatch),
+        (_, None) => {}
+    }
+    Ok(asset)
+}
+
+/// Checks that `signer` can update a plugin with the given authority.
+/// `UpdateAuthority` is left to mpl-core since update delegates can act on its behalf.
+pub fn validate_plugin_authority(
+    authority: &PluginAuthority,
+    asset: &BaseAssetV1,
+    signer: &Pubkey,
+) -> Result<()> {
+    match authority {
+        PluginAuthority::None => err!(ErrorCode::ImmutablePlugin),
+        PluginAuthority::Owner => {
+            require_keys_eq!(*signer, asset.owner, ErrorCode::UnauthorizedSigner);
+            Ok(())
+        }
+        PluginAuthority::Address { address } => {
+            require_keys_eq!(*signer, *address, ErrorCode::UnauthorizedSigner);
+            Ok(())
+        }
+        PluginAuthority::UpdateAuthority => Ok(()),
+    }
+}
+
+/// Collection counterpart of [`validate_plugin_authority`]. Collections have no owner,
+/// so an `Owner` authority can never be satisfied.
+pub fn validate_collection_plugin_authority(
+    authority: &PluginAuthority,
+    signer: &Pubkey,
+) -> Result<()> {
+    match authority {
+        PluginAuthority::None => err!(ErrorCode::ImmutablePlugin),
+        PluginAuthority::Owner => err!(ErrorCode::InvalidPluginAuthority),
+        PluginAuthority::Address { address } => {
+            require_keys_eq!(*signer, *address, ErrorCode::UnauthorizedSigner);
+            Ok(())
+        }
+        PluginAuthority::UpdateAuthority => Ok(()),
+    }
+}
+
+/// Checks that `signer` is the update authority of a standalone asset or one of its update delegates.
+/// For an asset in a collection use [`validate_collection_update_authority`] instead.
+pub fn validate_asset_update_authority(
+    asset_info: &AccountInfo,
+    asset: &BaseAssetV1,
+    signer: &Pubkey,
+) -> Result<()> {
+    if asset.update_authority == UpdateAuthority::Address(*signer) {
+        return Ok(());
+    }
+    let delegate = fetch_asset_plugin::<UpdateDelegate>(asset_info, PluginType::UpdateDelegate);
+    require!(
+        is_update_delegate(delegate, signer),
+        ErrorCode::UnauthorizedSigner
+    );
+    Ok(())
+}
+
+/// Checks that `signer` is the update authority of the collection or one of its update delegates.
+pub fn validate_collection_update_authority(
+    collection: &AccountInfo,
+    signer: &Pubkey,
+) -> Result<BaseCollectionV1> {
+    let base = load_collection(collection)?;
+    if base.update_authority == *signer {
+        return Ok(base);
+    }
+    let delegate =
+        fetch_collection_plugin::<UpdateDelegate>(collection, PluginType::UpdateDelegate);
+    require!(
+        is_update_delegate(delegate, signer),
+        ErrorCode::UnauthorizedSigner
+    );
+    Ok(base)
+}
+
+fn is_update_delegate(
+    delegate: std::io::Result<(PluginAuthority, UpdateDelegate, usize)>,
+    signer: &Pubkey,
+) -> bool {
+    match delegate {
+        Ok((authority, update_delegate, _)) => {
+            authority == PluginAuthority::Address { address: *signer }
+                || update_delegate.additional_delegates.contains(signer)
+        }
+        Err(_) => false,
+    }
+}
+
+/// Checks the accounts of a CPI into a program-controlled collection
+/// and returns the collection authority PDA that has to sign it.
+pub fn validate_program_collection<'a, 'info>(
+    config: &Account<CollectionConfig>,
+    collection: Option<&AccountInfo>,
+    collection_authority: Option<&'a AccountInfo<'info>>,
+) -> Result<&'a AccountInfo<'info>> {
+    let collection = collection.ok_or(ErrorCode::MissingCollection)?;
+    require_keys_eq!(
+        *collection.key,
+        config.collection,
+        ErrorCode::CollectionMismatch
+    );
+    let collection_authority = collection_authority.ok_or(ErrorCode::InvalidCollectionAuthority)?;
+    config.validate_collection_authority(config.to_account_info().key, collection_authority.key)?;
+    Ok(collection_authority)
+}
+
+/// Runs a CPI on a collection that may be program-controlled. Without a config, `invoke` gets
+/// `authority` and no seeds. With one, the config authority must have signed as `authority`,
+/// and `invoke` gets the collection authority PDA and the seeds to sign for it.
+pub fn invoke_as_collection_authority<'a, 'info>(
+    config: Option<&Account<CollectionConfig>>,
+    collection: Option<&AccountInfo>,
+    authority: Option<&'a AccountInfo<'info>>,
+    collection_authority: Option<&'a AccountInfo<'info>>,
+    invoke: impl FnOnce(Option<&'a AccountInfo<'info>>, &[&[&[u8]]]) -> ProgramResult,
+) -> Result<()> {
+    let Some(config) = config else {
+        invoke(authority, &[])?;
+        return Ok(());
+    };
+    config.validate_authority(
+        authority
+            .filter(|authority| authority.is_signer)
+            .map(|authority| authority.key),
+    )?;
+    let collection_authority =
+        validate_program_collection(config, collection, collection_authority)?;
+
+    let config_key = *config.to_account_info().key;
+    let bump = [config.collection_authority_bump];
+    invoke(
+        Some(collection_authority),
+        &[&CollectionConfig::collection_authority_seeds(
+            &config_key,
+            &bump,
+        )],
+    )?;
+    Ok(())
+}
+
+/// Checks the allowlist proof of `minter` when the program-controlled collection has an allowlist,
+/// returning the allocation of the wallet.
+pub fn validate_allowlist(
+    config: &CollectionConfig,
+    minter: &Pubkey,
+    proof: Option<&AllowlistProof>,
+) -> Result<Option<u32>> {
+    let Some(root) = &config.allowlist_root else {
+        require!(proof.is_none(), ErrorCode::AllowlistNotSet);
+        return Ok(None);
+    };
+    validate_allowlist_proof(root, minter, proof).map(Some)
+}
+
+/// Checks who may mint into a program-controlled collection, returning the owner of the new asset
+/// and its allocation. With an allowlist proof the listed owner mints for itself and has to sign,
+/// otherwise `authority` must be the config authority.
+pub fn validate_program_mint<'a, 'info>(
+    config: &CollectionConfig,
+    authority: Option<&'a AccountInfo<'info>>,
+    owner: Option<&'a AccountInfo<'info>>,
+    allowlist: Option<&AllowlistProof>,
+) -> Result<(&'a AccountInfo<'info>, Option<u32>)> {
+    let owner = if allowlist.is_some() {
+        owner
+            .filter(|owner| owner.is_signer)
+            .ok_or(ErrorCode::UnauthorizedSigner)?
+    } else {
+        config.validate_authority(authority.map(|authority| authority.key))?;
+        // The collection authority PDA signs in place of `authority`, so the owner can't default to it
+        owner.or(authority).ok_or(ErrorCode::UnauthorizedSigner)?
+    };
+    let allocation = validate_allowlist(config, owner.key, allowlist)?;
+    Ok((owner, allocation))
+}
+
+/// Checks that `minter` is on the allowlist with the given root, returning its allocation.
+pub fn validate_allowlist_proof(
+    root: &[u8; 32],
+    minter: &Pubkey,
+    proof: Option<&AllowlistProof>,
+) -> Result<u32> {
+    let proof = proof.ok_or(ErrorCode::AllowlistProofRequired)?;
+    require!(
+        verify_proof(&proof.proof, root, allowlist_leaf(minter, proof.allocation)),
+        ErrorCode::InvalidAllowlistProof
+    );
+    Ok(proof.allocation)
+}
+
+/// Checks that `holder` owns `key_asset` and that the asset is part of `collection`.
+pub fn validate_holder(
+    key_asset: &AccountInfo,
+    holder: &Pubkey,
+    collection: &Pubkey,
+) -> Result<BaseAssetV1> {
+    let asset = load_asset(key_asset)?;
+    require_keys_eq!(asset.owner, *holder, ErrorCode::NotAHolder);
+    require!(
+        asset.update_authority == UpdateAuthority::Collection(*collection),
+        ErrorCode::NotAHolder
+    );
+    Ok(asset)
+}
@@ -5,6 +5,7 @@ import {fetchAsset, mplCore} from "@metaplex-foundation/mpl-core";
 import { Umi,  createSignerFromKeypair, signerIdentity } from "@metaplex-foundation/umi";
 import { createUmi } from '@metaplex-foundation/umi-bundle-defaults';
 import { assert } from "chai";
+import { createHash } from "crypto";
 
 describe("mpl-core-examples", () => {
   // Configure the client to use the local cluster.
@@ -51,6 +52,70 @@ describe("mpl-core-examples", () => {
     collection=anchor.web3.Keypair.generate();
   });
 
+  const TOKEN_PROGRAM_ID = new anchor.web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+  const TOKEN_2022_PROGRAM_ID = new anchor.web3.PublicKey("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+  const ASSOCIATED_TOKEN_PROGRAM_ID = new anchor.web3.PublicKey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+  const associatedTokenAddress = (owner: anchor.web3.PublicKey, mint: anchor.web3.PublicKey, tokenProgram = TOKEN_PROGRAM_ID) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [owner.toBuffer(), tokenProgram.toBuffer(), mint.toBuffer()],
+      ASSOCIATED_TOKEN_PROGRAM_ID
+    )[0];
+
+  // Creates a token with `payer` as mint authority and mints `amount` to its token account.
+  // The token instructions are built by hand to keep the tests free of an spl-token dependency.
+  // They are the same in Token-2022 for a mint without extensions.
+  const createMint = async (amount: number, tokenProgram = TOKEN_PROGRAM_ID) => {
+    const mint = anchor.web3.Keypair.generate();
+    const payerTokenAccount = associatedTokenAddress(payer.publicKey, mint.publicKey, tokenProgram);
+    const amountData = Buffer.alloc(8);
+    amountData.writeBigUInt64LE(BigInt(amount));
+
+    const tx = new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.createAccount({
+        fromPubkey: payer.publicKey,
+        newAccountPubkey: mint.publicKey,
+        lamports: await connection.getMinimumBalanceForRentExemption(82),
+        space: 82,
+        programId: tokenProgram,
+      }),
+      // InitializeMint2 with 0 decimals and no freeze authority
+      new anchor.web3.TransactionInstruction({
+        programId: tokenProgram,
+        keys: [{ pubkey: mint.publicKey, isSigner: false, isWritable: true }],
+        data: Buffer.concat([Buffer.from([20, 0]), payer.publicKey.toBuffer(), Buffer.from([0])]),
+      }),
+      createTokenAccountInstruction(payer.publicKey, mint.publicKey, tokenProgram),
+      // MintTo
+      new anchor.web3.TransactionInstruction({
+        programId: tokenProgram,
+        keys: [
+          { pubkey: mint.publicKey, isSigner: false, isWritable: true },
+          { pubkey: payerTokenAccount, isSigner: false, isWritable: true },
+          { pubkey: payer.publicKey, isSigner: true, isWritable: false },
+        ],
+        data: Buffer.concat([Buffer.from([7]), amountData]),
+      }),
+    );
+    await provider.sendAndConfirm(tx, [mint]).then(log);
+    return { mint: mint.publicKey, payerTokenAccount };
+  };
+
+  // Idempotent creation of the associated token account of `owner`
+  const createTokenAccountInstruction = (owner: anchor.web3.PublicKey, mint: anchor.web3.PublicKey, tokenProgram = TOKEN_PROGRAM_ID) =>
+    new anchor.web3.TransactionInstruction({
+      programId: ASSOCIATED_TOKEN_PROGRAM_ID,
+      keys: [
+        { pubkey: payer.publicKey, isSigner: true, isWritable: true },
+        { pubkey: associatedTokenAddress(owner, mint, tokenProgram), isSigner: false, isWritable: true },
+        { pubkey: owner, isSigner: false, isWritable: false },
+        { pubkey: mint, isSigner: false, isWritable: false },
+        { pubkey: anchor.web3.SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: tokenProgram, isSigner: false, isWritable: false },
+      ],
+      data: Buffer.from([1]),
+    });
+
   describe("Collection creattion", ()=>{
 
   it("should create a collection", async () => {
@@ -123,6 +188,119 @@ describe("mpl-core-examples", () => {
     
   })
 
+  it("should create a collection with royalties, permanent freeze delegate and oracle in one instruction", async()=>{
+    await program.methods.createCollectionWithPlugins({
+      name: "My NFT Collection",
+      uri: "https://example.com/collection.json",
+      plugins: [
+        {
+          plugin: {
+            royalties: {
+              basisPoints: 500,
+              creators: [{ address: payer.publicKey, percentage: 100 }],
+              ruleSet: { none: {} },
+            },
+          },
+          authority: null,
+        },
+        {
+          plugin: { permanentFreezeDelegate: { frozen: false } },
+          authority: { updateAuthority: {} },
+        },
+      ],
+      externalPluginAdapters: [
+        {
+          adapter: {
+            oracle: {
+              baseAddress: new anchor.web3.PublicKey("AwPRxL5f6GDVajyE1bBcfSWdQT58nWMoS36A1uFtpCZY"),
+              lifecycleChecks: [{ event: { transfer: {} }, flags: 4 }],
+              resultsOffset: { anchor: {} },
+            },
+          },
+          authority: null,
+        },
+      ],
+    }).accountsPartial({
+      payer: payer.publicKey,
+      collection: collection.publicKey,
+      updateAuthority: payer.publicKey,
+    }).signers([payer.payer, collection]).rpc().then(log);
+
+    console.log(`Collection: ${collection.publicKey.toBase58()}`);
+  })
+
+  it("should create a collection and add royalties managed by a delegate", async()=>{
+    await program.methods.createCollection({
+      name: "My NFT Collection",
+      uri: "https://example.com/collection.json",
+    }).accountsPartial({
+      payer: payer.publicKey,
+      collection: collection.publicKey,
+      updateAuthority: payer.publicKey,
+    }).signers([payer.payer, collection]).rpc().then(log);
+
+    await program.methods.addRoyaltiesPluginToCollection({
+      basisPoints: 500,
+      creators: [{ address: payer.publicKey, percentage: 100 }],
+      ruleSet: { none: {} },
+      delegate: { address: { address: anchor.web3.Keypair.generate().publicKey } },
+    }).accountsPartial({
+      payer: payer.publicKey,
+      collection: collection.publicKey,
+      updateAuthority: payer.publicKey,
+      logWrapper: null,
+    }).signers([payer.payer]).rpc().then(log);
+  })
+
+  it("should reject owner-managed plugins on a collection", async()=>{
+    try {
+      await program.methods.createCollectionWithPlugins({
+        name: "My NFT Collection",
+        uri: "https://example.com/collection.json",
+        plugins: [{ plugin: { freezeDelegate: { frozen: true } }, authority: null }],
+        externalPluginAdapters: [],
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      assert.fail("Owner-managed plugins should have been rejected");
+    } catch (err) {
+      assert.equal((err as anchor.AnchorError).error.errorCode.code, "PluginNotSupportedOnCollection");
+    }
+  })
+
+  it("should reject oracle lifecycle checks that do more than reject", async()=>{
+    try {
+      await program.methods.createCollectionWithPlugins({
+        name: "My NFT Collection",
+        uri: "https://example.com/collection.json",
+        plugins: [],
+        externalPluginAdapters: [
+          {
+            adapter: {
+              oracle: {
+                baseAddress: new anchor.web3.PublicKey("AwPRxL5f6GDVajyE1bBcfSWdQT58nWMoS36A1uFtpCZY"),
+                lifecycleChecks: [{ event: { transfer: {} }, flags: 2 }],
+                resultsOffset: { anchor: {} },
+              },
+            },
+            authority: null,
+          },
+        ],
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      assert.fail("Oracle checks other than can reject should have been rejected");
+    } catch (err) {
+      assert.equal((err as anchor.AnchorError).error.errorCode.code, "InvalidLifecycleCheckFlags");
+    }
+  })
+
   })
 
   describe("Master Edition flow",()=>{
@@ -173,6 +351,174 @@ describe("mpl-core-examples", () => {
     console.log(`Asset: ${asset.publicKey.toBase58()}`)
     console.log(`Collection: ${collection.publicKey.toBase58()}`); 
   })
+
+  it("should reject an edition once the max supply has been minted", async()=>{
+    await program.methods.createMasterEdition({
+      nameOfMasterEditionCollection: "My Master Edition Collection",
+      uriOfMasterEditionCollection: "https://example.com/collection.json",
+      maxSupply: 2,
+      masterEditionName:"Test Master Edition",
+      masterEditionUri:"https://example.com/edition.json",
+    }).accountsPartial({
+      payer: payer.publicKey,
+      collection: collection.publicKey,
+      updateAuthority: payer.publicKey,
+    }).signers([payer.payer, collection]).rpc().then(log);
+
+    const mintEdition = async (editionAsset: anchor.web3.Keypair) => {
+      await program.methods.createEdition({
+        nameOfEditionAsset: "My Edition",
+        uriOfEditionAsset: "https://example.com/edition1.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        masterEditionCollection: collection.publicKey,
+        authority: payer.publicKey,
+        editionAsset: editionAsset.publicKey,
+        owner: payer.publicKey,
+      }).signers([editionAsset, payer.payer]).rpc().then(log);
+    };
+
+    // editions #1 and #2 fill the supply exactly
+    await mintEdition(anchor.web3.Keypair.generate());
+    await mintEdition(anchor.web3.Keypair.generate());
+
+    // edition #3 is one past the cap
+    try {
+      await mintEdition(anchor.web3.Keypair.generate());
+      assert.fail("Minting past the max supply should have failed");
+    } catch (err) {
+      assert.equal((err as anchor.AnchorError).error.errorCode.code, "EditionSupplyExhausted");
+    }
+  })
+
+  it("should keep minting editions of an open edition", async()=>{
+    await program.methods.createCollectionWithPlugins({
+      name: "My Open Edition Collection",
+      uri: "https://example.com/collection.json",
+      plugins: [
+        {
+          plugin: { masterEdition: { maxSupply: null, name: null, uri: null } },
+          authority: null,
+        },
+      ],
+      externalPluginAdapters: [],
+    }).accountsPartial({
+      payer: payer.publicKey,
+      collection: collection.publicKey,
+      updateAuthority: payer.publicKey,
+    }).signers([payer.payer, collection]).rpc().then(log);
+
+    for (let i = 0; i < 3; i++) {
+      const editionAsset = anchor.web3.Keypair.generate();
+      await program.methods.createEdition({
+        nameOfEditionAsset: `My Open Edition #${i + 1}`,
+        uriOfEditionAsset: "https://example.com/edition1.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        masterEditionCollection: collection.publicKey,
+        authority: payer.publicKey,
+        editionAsset: editionAsset.publicKey,
+        owner: payer.publicKey,
+      }).signers([editionAsset, payer.payer]).rpc().then(log);
+    }
+  })
+
+  it("should burn an empty master edition collection and reclaim the edition counter rent", async()=>{
+    await program.methods.createMasterEdition({
+      nameOfMasterEditionCollection: "My Master Edition Collection",
+      uriOfMasterEditionCollection: "https://example.com/collection.json",
+      maxSupply: 10,
+      masterEditionName: null,
+      masterEditionUri: null,
+    }).accountsPartial({
+      payer: payer.publicKey,
+      collection: collection.publicKey,
+      updateAuthority: payer.publicKey,
+    }).signers([payer.payer, collection]).rpc().then(log);
+
+    const editionAsset = anchor.web3.Keypair.generate();
+    await program.methods.createEdition({
+      nameOfEditionAsset: "My Edition",
+      uriOfEditionAsset: "https://example.com/edition1.json",
+    }).accountsPartial({
+      payer: payer.publicKey,
+      masterEditionCollection: collection.publicKey,
+      authority: payer.publicKey,
+      editionAsset: editionAsset.publicKey,
+      owner: payer.publicKey,
+    }).signers([editionAsset, payer.payer]).rpc().then(log);
+
+    const [editionCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("edition_counter"), collection.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const recipient = anchor.web3.Keypair.generate().publicKey;
+    const burnCollection = () => program.methods.burnCollection().accountsPartial({
+      payer: payer.publicKey,
+      collection: collection.publicKey,
+      authority: payer.publicKey,
+      recipient,
+      editionCounter,
+      logWrapper: null,
+    }).signers([payer.payer]).rpc().then(log);
+
+    // the edition is still a member of the collection
+    try {
+      await burnCollection();
+      assert.fail("a collection with assets can not be burned");
+    } catch (err) {
+      assert.equal((err as anchor.AnchorError).error.errorCode.code, "CollectionNotEmpty");
+    }
+
+    await program.methods.burnNft().accountsPartial({
+      payer: payer.publicKey,
+      asset: editionAsset.publicKey,
+      collection: collection.publicKey,
+      authority: payer.publicKey
+    }).signers([payer.payer]).rpc().then(log);
+
+    const collectionLamports = (await connection.getAccountInfo(collection.publicKey)).lamports;
+    const counterLamports = (await connection.getAccountInfo(editionCounter)).lamports;
+
+    await burnCollection();
+    assert.isNull(await connection.getAccountInfo(editionCounter));
+
+    // the recipient gets the rent of the counter and whatever mpl-core released from the collection
+    const collectionLeft = (await connection.getAccountInfo(collection.publicKey))?.lamports ?? 0;
+    assert.equal(
+      await connection.getBalance(recipient),
+      counterLamports + collectionLamports - collectionLeft
+    );
+  })
+
+  it("should burn a collection whose update authority is not the payer", async()=>{
+    const authority = anchor.web3.Keypair.generate();
+    await program.methods.createCollection({
+      name: "My NFT Collection",
+      uri: "https://example.com/collection.json",
+    }).accountsPartial({
+      payer: payer.publicKey,
+      collection: collection.publicKey,
+      updateAuthority: authority.publicKey,
+    }).signers([payer.payer, collection]).rpc().then(log);
+
+    const collectionLamports = (await connection.getAccountInfo(collection.publicKey)).lamports;
+    const recipient = anchor.web3.Keypair.generate().publicKey;
+    await program.methods.burnCollection().accountsPartial({
+      payer: payer.publicKey,
+      collection: collection.publicKey,
+      authority: authority.publicKey,
+      recipient,
+      editionCounter: null,
+      logWrapper: null,
+    }).signers([payer.payer, authority]).rpc().then(log);
+
+    // the rent mpl-core paid out to the authority is forwarded to the recipient
+    const collectionLeft = (await connection.getAccountInfo(collection.publicKey))?.lamports ?? 0;
+    assert.equal(await connection.getBalance(recipient), collectionLamports - collectionLeft);
+    assert.equal(await connection.getBalance(authority.publicKey), 0);
+  })
   })
 
   describe("Covers the various asset creation flows",()=>{
@@ -268,6 +614,60 @@ describe("mpl-core-examples", () => {
 
   })
 
+  it("should create a nft/asset with a permanent freeze delegate and royalties in one instruction", async()=>{
+    await program.methods.createNftWithPlugins({
+      name: "My NFT",
+      uri: "https://example.com/nft.json",
+      plugins: [
+        {
+          plugin: { permanentFreezeDelegate: { frozen: true } },
+          authority: { updateAuthority: {} },
+        },
+        {
+          plugin: {
+            royalties: {
+              basisPoints: 500,
+              creators: [{ address: payer.publicKey, percentage: 100 }],
+              ruleSet: { none: {} },
+            },
+          },
+          authority: null,
+        },
+      ],
+  }).accountsPartial({
+    payer: payer.publicKey,
+    asset: asset.publicKey,
+    updateAuthority: payer.publicKey,
+    authority: payer.publicKey,
+    collection: null,
+    owner: payer.publicKey,
+  }).signers([payer.payer, asset]).rpc().then(log);
+  })
+
+  it("should reject duplicate plugins when creating a nft/asset with plugins", async()=>{
+    try {
+      await program.methods.createNftWithPlugins({
+        name: "My NFT",
+        uri: "https://example.com/nft.json",
+        plugins: [
+          { plugin: { permanentBurnDelegate: {} }, authority: null },
+          { plugin: { permanentBurnDelegate: {} }, authority: null },
+        ],
+    }).accountsPartial({
+      payer: payer.publicKey,
+      asset: asset.publicKey,
+      updateAuthority: payer.publicKey,
+      authority: payer.publicKey,
+      collection: null,
+      owner: payer.publicKey,
+    }).signers([payer.payer, asset]).rpc().then(log);
+
+      assert.fail("Duplicate plugins should have been rejected");
+    } catch (err) {
+      assert.equal((err as anchor.AnchorError).error.errorCode.code, "DuplicatePlugin");
+    }
+  })
+
   })
 
   describe("Covers various asset/nft operation like burn and transfer",()=>{
@@ -346,6 +746,115 @@ describe("mpl-core-examples", () => {
   })
   })
 
+  describe("Covers the validation done before reaching mpl core",()=>{
+    it("should reject a name longer than the maximum length", async()=>{
+      try {
+        await program.methods.createNft({
+          name: "A".repeat(33),
+          uri: "https://example.com/nft.json",
+        }).accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          updateAuthority: payer.publicKey,
+          authority: payer.publicKey,
+          collection: null,
+          owner: payer.publicKey,
+        }).signers([payer.payer, asset]).rpc().then(log);
+
+        assert.fail("An oversized name should have been rejected");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "NameTooLong");
+      }
+    })
+
+    it("should reject a URI longer than the maximum length", async()=>{
+      try {
+        await program.methods.createNft({
+          name: "My NFT",
+          uri: "https://example.com/" + "a".repeat(200),
+        }).accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          updateAuthority: payer.publicKey,
+          authority: payer.publicKey,
+          collection: null,
+          owner: payer.publicKey,
+        }).signers([payer.payer, asset]).rpc().then(log);
+
+        assert.fail("An oversized URI should have been rejected");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "UriTooLong");
+      }
+    })
+
+    it("should reject royalties whose creator shares do not add up to 100", async()=>{
+      await program.methods.createNft({
+        name: "My NFT",
+        uri: "https://example.com/nft.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        updateAuthority: payer.publicKey,
+        authority: payer.publicKey,
+        collection: null,
+        owner: payer.publicKey,
+      }).signers([payer.payer, asset]).rpc().then(log);
+
+      try {
+        await program.methods.addRoyaltiesPlugin({
+          basisPoints: 500,
+          creators: [{ address: payer.publicKey, percentage: 60 }],
+          ruleSet: { none: {} },
+        }).accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: null,
+          authority: payer.publicKey,
+        }).signers([payer.payer]).rpc().then(log);
+
+        assert.fail("Invalid creator shares should have been rejected");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "InvalidCreatorShares");
+      }
+    })
+
+    it("should reject an asset transfer without its collection", async()=>{
+      await program.methods.createCollection({
+        name: "My NFT Collection",
+        uri: "https://example.com/collection.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      await program.methods.createNft({
+        name: "My NFT",
+        uri: "https://example.com/nft.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        owner: payer.publicKey,
+        updateAuthority: null,
+      }).signers([payer.payer, asset]).rpc().then(log);
+
+      try {
+        await program.methods.transferNft().accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: null,
+          newOwner: anchor.web3.Keypair.generate().publicKey,
+        }).signers([payer.payer]).rpc().then(log);
+
+        assert.fail("Transfer without the collection should have been rejected");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "MissingCollection");
+      }
+    })
+  })
+
   describe("Covers various cases of adding plugins at an asset level",()=>{
     it("should create asset and add royalties plugin",async()=>{
       await program.methods.createNft({
@@ -368,7 +877,8 @@ describe("mpl-core-examples", () => {
             address: payer.publicKey,
             percentage: 100
           }
-        ]
+        ],
+        ruleSet: { none: {} }
       }).accountsPartial({
         payer: payer.publicKey,
         asset: asset.publicKey,
@@ -378,10 +888,9 @@ describe("mpl-core-examples", () => {
 
     })
 
-
-    it("should create asset and add autograph plugin",async()=>{
+    it("should add a royalties plugin without a rule set like before rule sets existed",async()=>{
       await program.methods.createNft({
-          name: "My NFT",
+        name: "My NFT",
         uri: "https://example.com/nft.json"
       }).accountsPartial({
         payer: payer.publicKey,
@@ -392,19 +901,32 @@ describe("mpl-core-examples", () => {
         owner: payer.publicKey,
       }).signers([payer.payer, asset]).rpc().then(log)
 
-      // Add autograph plugin
-      await program.methods.addAutographPlugin({
-        message: "Initialized message"
+      // `ruleSet` and `delegate` are optional and left out, as older clients do
+      await program.methods.addRoyaltiesPlugin({
+        basisPoints: 600,
+        creators: [{ address: payer.publicKey, percentage: 100 }],
+      } as any).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority: payer.publicKey,
+      }).signers([payer.payer]).rpc().then(log)
+
+      await program.methods.updateRoyaltiesPlugin({
+        basisPoints: 500,
+        creators: [{ address: payer.publicKey, percentage: 100 }],
+        ruleSet: null,
       }).accountsPartial({
         payer: payer.publicKey,
         asset: asset.publicKey,
         collection: null,
-        authority:payer.publicKey,
+        authority: payer.publicKey,
+        logWrapper: null,
       }).signers([payer.payer]).rpc().then(log)
     })
 
-    it("should create asset and add royalty plugin, the update the royalties in another tx",async()=>{
-      // 1. Create asset
+
+    it("should create asset and add a freeze delegate held by the program PDA",async()=>{
       await program.methods.createNft({
           name: "My NFT",
         uri: "https://example.com/nft.json"
@@ -417,9 +939,162 @@ describe("mpl-core-examples", () => {
         owner: payer.publicKey,
       }).signers([payer.payer, asset]).rpc().then(log)
 
-      // 2. Add royalties plugin
-      const creator2= anchor.web3.Keypair.generate()
-      await program.methods.addRoyaltiesPlugin({
+      await program.methods.addFreezeDelegatePlugin({
+        delegate: { program: {} },
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority:payer.publicKey,
+      }).signers([payer.payer]).rpc().then(log)
+
+      // the program PDA signs the thaw, so the frozen asset can move again
+      await program.methods.thawWithProgramDelegate().accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority: payer.publicKey,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log)
+
+      await program.methods.transferNft().accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        newOwner: anchor.web3.Keypair.generate().publicKey,
+      }).signers([payer.payer]).rpc().then(log)
+    })
+
+    it("should give a frozen freeze delegate back from the program PDA to the owner",async()=>{
+      await program.methods.createNft({
+          name: "My NFT",
+        uri: "https://example.com/nft.json"
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        updateAuthority: payer.publicKey,
+        authority: payer.publicKey,
+        collection: null,
+        owner: payer.publicKey,
+      }).signers([payer.payer, asset]).rpc().then(log)
+
+      await program.methods.addFreezeDelegatePlugin({
+        delegate: { program: {} },
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority:payer.publicKey,
+      }).signers([payer.payer]).rpc().then(log)
+
+      await program.methods.revokeProgramDelegate({
+        pluginType: { freezeDelegate: {} },
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority: payer.publicKey,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log)
+
+      try {
+        await program.methods.thawWithProgramDelegate().accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: null,
+          authority: payer.publicKey,
+          logWrapper: null,
+        }).signers([payer.payer]).rpc().then(log)
+        assert.fail("The program PDA should not hold the freeze delegate anymore");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "InvalidPluginAuthority");
+      }
+
+      // revoking thawed the asset first
+      await program.methods.transferNft().accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        newOwner: anchor.web3.Keypair.generate().publicKey,
+      }).signers([payer.payer]).rpc().then(log)
+    })
+
+    it("should create asset and add transfer and burn delegates for another wallet",async()=>{
+      await program.methods.createNft({
+          name: "My NFT",
+        uri: "https://example.com/nft.json"
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        updateAuthority: payer.publicKey,
+        authority: payer.publicKey,
+        collection: null,
+        owner: payer.publicKey,
+      }).signers([payer.payer, asset]).rpc().then(log)
+
+      const delegate = anchor.web3.Keypair.generate().publicKey;
+
+      await program.methods.addTransferDelegatePlugin({
+        delegate: { address: { address: delegate } },
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority:payer.publicKey,
+      }).signers([payer.payer]).rpc().then(log)
+
+      // without a delegate the owner keeps the authority
+      await program.methods.addBurnDelegatePlugin({
+        delegate: null,
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority:payer.publicKey,
+      }).signers([payer.payer]).rpc().then(log)
+    })
+
+    it("should create asset and add autograph plugin",async()=>{
+      await program.methods.createNft({
+          name: "My NFT",
+        uri: "https://example.com/nft.json"
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        updateAuthority: payer.publicKey,
+        authority: payer.publicKey,
+        collection: null,
+        owner: payer.publicKey,
+      }).signers([payer.payer, asset]).rpc().then(log)
+
+      // Add autograph plugin
+      await program.methods.addAutographPlugin({
+        message: "Initialized message"
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority:payer.publicKey,
+      }).signers([payer.payer]).rpc().then(log)
+    })
+
+    it("should create asset and add royalty plugin, the update the royalties in another tx",async()=>{
+      // 1. Create asset
+      await program.methods.createNft({
+          name: "My NFT",
+        uri: "https://example.com/nft.json"
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        updateAuthority: payer.publicKey,
+        authority: payer.publicKey,
+        collection: null,
+        owner: payer.publicKey,
+      }).signers([payer.payer, asset]).rpc().then(log)
+
+      // 2. Add royalties plugin
+      const creator2= anchor.web3.Keypair.generate()
+      await program.methods.addRoyaltiesPlugin({
         basisPoints: 500,
         creators:[
           {
@@ -427,7 +1102,8 @@ describe("mpl-core-examples", () => {
             percentage: 100
           },
         
-        ]
+        ],
+        ruleSet: { none: {} }
       }).accountsPartial({
         payer: payer.publicKey,
         asset: asset.publicKey,
@@ -448,6 +1124,7 @@ describe("mpl-core-examples", () => {
             percentage: 50
           }
         ],
+        ruleSet: { none: {} }
       
       }).accountsPartial({
         payer: payer.publicKey,
@@ -457,6 +1134,42 @@ describe("mpl-core-examples", () => {
       }).signers([payer.payer]).rpc().then(log)
     })
 
+    it("should create asset with a royalties allow list, then switch it to a deny list without resending creators",async()=>{
+      await program.methods.createNft({
+          name: "My NFT",
+        uri: "https://example.com/nft.json"
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        updateAuthority: payer.publicKey,
+        authority: payer.publicKey,
+        collection: null,
+        owner: payer.publicKey,
+      }).signers([payer.payer, asset]).rpc().then(log)
+
+      const marketplace = anchor.web3.Keypair.generate().publicKey;
+
+      await program.methods.addRoyaltiesPlugin({
+        basisPoints: 500,
+        creators: [{ address: payer.publicKey, percentage: 100 }],
+        ruleSet: { programAllowList: [[program.programId]] },
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority:payer.publicKey,
+      }).signers([payer.payer]).rpc().then(log)
+
+      await program.methods.updateRoyaltiesRuleSet({
+        ruleSet: { programDenyList: [[marketplace]] },
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority:payer.publicKey,
+      }).signers([payer.payer]).rpc().then(log)
+    })
+
     it("should create asset and add autograph plugin, then update the message in another tx",async()=>{
       // 1. Create asset
       await program.methods.createNft({
@@ -479,14 +1192,1951 @@ describe("mpl-core-examples", () => {
       authority:payer.publicKey,
     }).signers([payer.payer]).rpc().then(log)
 
-    // 3. Update autograph plugin
+    // 3. Another wallet appends its own autograph
+    const fan = anchor.web3.Keypair.generate();
     await program.methods.addNewAutographToAssetWithExistingAutographPlugin({ message: "Updated message"}).accountsPartial({
       payer: payer.publicKey,
       asset: asset.publicKey,
       collection: null,
-      authority:payer.publicKey,
-    }).signers([payer.payer]).rpc().then(log)
+      authority:fan.publicKey,
+    }).signers([payer.payer, fan]).rpc().then(log)
+
+    // 4. The same wallet can not sign twice
+    try {
+      await program.methods.addNewAutographToAssetWithExistingAutographPlugin({ message: "Again"}).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority:fan.publicKey,
+      }).signers([payer.payer, fan]).rpc()
+      assert.fail("a second autograph from the same address should be rejected")
+    } catch (err) {
+      assert.equal((err as anchor.AnchorError).error.errorCode.code, "DuplicateAutograph")
+    }
+
+    // 5. The fan removes their autograph with the owner's approval
+    await program.methods.removeOwnAutograph().accountsPartial({
+      payer: payer.publicKey,
+      asset: asset.publicKey,
+      collection: null,
+      autographer: fan.publicKey,
+      authority: payer.publicKey,
+      logWrapper: null,
+    }).signers([payer.payer, fan]).rpc().then(log)
+
+    })
+
+    it("should reject an autograph message longer than the maximum length",async()=>{
+      await program.methods.createNft({
+          name: "My NFT",
+        uri: "https://example.com/nft.json"
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        updateAuthority: payer.publicKey,
+        authority: payer.publicKey,
+        collection: null,
+        owner: payer.publicKey,
+      }).signers([payer.payer, asset]).rpc().then(log)
+
+      try {
+        await program.methods.addAutographPlugin({ message: "a".repeat(141) }).accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: null,
+          authority:payer.publicKey,
+        }).signers([payer.payer]).rpc()
+        assert.fail("the message should be too long")
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "AutographMessageTooLong")
+      }
+    })
+  })
+
+  describe("Covers removing plugins from assets and collections",()=>{
+    it("should add a royalties plugin to an asset, remove it, then fail to remove it again",async()=>{
+      await program.methods.createNft({
+          name: "My NFT",
+        uri: "https://example.com/nft.json"
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        updateAuthority: payer.publicKey,
+        authority: payer.publicKey,
+        collection: null,
+        owner: payer.publicKey,
+      }).signers([payer.payer, asset]).rpc().then(log)
+
+      await program.methods.addRoyaltiesPlugin({
+        basisPoints: 500,
+        creators: [{ address: payer.publicKey, percentage: 100 }],
+        ruleSet: { none: {} },
+        delegate: null,
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority:payer.publicKey,
+      }).signers([payer.payer]).rpc().then(log)
+
+      await program.methods.removeNftPlugin({
+        pluginType: { royalties: {} },
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority: payer.publicKey,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log)
+
+      try {
+        await program.methods.removeNftPlugin({
+          pluginType: { royalties: {} },
+        }).accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: null,
+          authority: payer.publicKey,
+          logWrapper: null,
+        }).signers([payer.payer]).rpc()
+        assert.fail("the plugin was already removed")
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "PluginNotFound")
+      }
+    })
+
+    it("should remove the oracle adapter from a collection",async()=>{
+      await program.methods.createCollectionWithOraclePlugin({
+        name: "My NFT",
+        uri: "https://example.com/collection.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      await program.methods.removeCollectionExternalPluginAdapter({
+        key: { oracle: { baseAddress: new anchor.web3.PublicKey("AwPRxL5f6GDVajyE1bBcfSWdQT58nWMoS36A1uFtpCZY") } },
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+    })
+  })
+
+  describe("Covers approving and revoking plugin authorities",()=>{
+    it("should hand transfer and burn delegation to a marketplace, then revoke both at once",async()=>{
+      await program.methods.createNft({
+          name: "My NFT",
+        uri: "https://example.com/nft.json"
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        updateAuthority: payer.publicKey,
+        authority: payer.publicKey,
+        collection: null,
+        owner: payer.publicKey,
+      }).signers([payer.payer, asset]).rpc().then(log)
+
+      for (const method of ["addTransferDelegatePlugin", "addBurnDelegatePlugin"] as const) {
+        await program.methods[method]({ delegate: null }).accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: null,
+          authority:payer.publicKey,
+        }).signers([payer.payer]).rpc().then(log)
+      }
+
+      const marketplace = anchor.web3.Keypair.generate().publicKey;
+      for (const pluginType of [{ transferDelegate: {} }, { burnDelegate: {} }]) {
+        await program.methods.approveNftPluginAuthority({
+          pluginType,
+          newAuthority: { address: { address: marketplace } },
+        }).accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: null,
+          authority: payer.publicKey,
+          logWrapper: null,
+        }).signers([payer.payer]).rpc().then(log)
+      }
+
+      await program.methods.revokeAllOwnerManagedDelegates().accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority: payer.publicKey,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log)
+    })
+
+    it("should refuse to make an owner-managed plugin immutable",async()=>{
+      await program.methods.createNft({
+          name: "My NFT",
+        uri: "https://example.com/nft.json"
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        updateAuthority: payer.publicKey,
+        authority: payer.publicKey,
+        collection: null,
+        owner: payer.publicKey,
+      }).signers([payer.payer, asset]).rpc().then(log)
+
+      try {
+        await program.methods.approveNftPluginAuthority({
+          pluginType: { transferDelegate: {} },
+          newAuthority: { none: {} },
+        }).accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: null,
+          authority: payer.publicKey,
+          logWrapper: null,
+        }).signers([payer.payer]).rpc()
+        assert.fail("owner-managed plugins can not be made immutable")
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "InvalidPluginAuthority")
+      }
+    })
+  })
+
+  describe("Covers updating plugins at collection level",()=>{
+    it("should thaw and freeze every asset of a collection by toggling its permanent freeze delegate",async()=>{
+      // the collection starts frozen
+      await program.methods.createCollectionWithPermanentFreezeDelegate({
+        name: "My NFT Collection",
+        uri: "https://example.com/collection.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      await program.methods.createNft({
+        name: "My NFT",
+        uri: "https://example.com/nft.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        owner: payer.publicKey,
+        updateAuthority:null
+      }).signers([payer.payer, asset]).rpc().then(log);
+
+      const toggle = () => program.methods.updateCollectionPermanentFreezePlugin().accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+
+      const transfer = (newOwner: anchor.web3.PublicKey) => program.methods.transferNft().accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        newOwner,
+      }).signers([payer.payer]).rpc().then(log);
+
+      // thaw the whole collection, the asset can move
+      await toggle();
+      await transfer(payer.publicKey);
+
+      // freeze it again, transfers are blocked
+      await toggle();
+      let transferred = false;
+      try {
+        await transfer(anchor.web3.Keypair.generate().publicKey);
+        transferred = true;
+      } catch (err) {
+        console.log(err);
+      }
+      assert.isFalse(transferred, "the asset should be frozen by its collection")
+    })
+
+    it("should add royalties to a collection and then update them",async()=>{
+      await program.methods.createCollection({
+        name: "My NFT Collection",
+        uri: "https://example.com/collection.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      await program.methods.addRoyaltiesPluginToCollection({
+        basisPoints: 500,
+        creators: [{ address: payer.publicKey, percentage: 100 }],
+        ruleSet: { none: {} },
+        delegate: null,
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+
+      const creator = anchor.web3.Keypair.generate().publicKey;
+      await program.methods.updateCollectionRoyaltiesPlugin({
+        basisPoints: 750,
+        creators: [
+          { address: payer.publicKey, percentage: 60 },
+          { address: creator, percentage: 40 },
+        ],
+        ruleSet: { none: {} },
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+    })
+  })
+
+  describe("Covers collection metadata and update authority handover",()=>{
+    const createCollection = () => program.methods.createCollection({
+      name: "My NFT Collection",
+      uri: "https://example.com/collection.json",
+    }).accountsPartial({
+      payer: payer.publicKey,
+      collection: collection.publicKey,
+      updateAuthority: payer.publicKey,
+    }).signers([payer.payer, collection]).rpc().then(log);
+
+    it("should rename a collection and keep its uri",async()=>{
+      await createCollection();
+
+      await program.methods.updateCollection({
+        name: "Renamed Collection",
+        uri: null,
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        newUpdateAuthority: null,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+    })
+
+    it("should hand the update authority over once the new authority accepts",async()=>{
+      await createCollection();
+      const newAuthority = anchor.web3.Keypair.generate();
+
+      await program.methods.proposeCollectionUpdateAuthority({
+        newAuthority: newAuthority.publicKey,
+      }).accountsPartial({
+        authority: payer.publicKey,
+        collection: collection.publicKey,
+      }).signers([payer.payer]).rpc().then(log);
+
+      // the new authority pays the mpl core CPI when accepting
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: newAuthority.publicKey,
+          lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+        })
+      ));
+
+      await program.methods.acceptCollectionUpdateAuthority().accountsPartial({
+        newAuthority: newAuthority.publicKey,
+        proposer: payer.publicKey,
+        collection: collection.publicKey,
+      }).signers([newAuthority]).rpc().then(log);
+    })
+
+    it("should give the update authority back when a mistyped proposal is cancelled",async()=>{
+      await createCollection();
+      const typo = anchor.web3.Keypair.generate();
+
+      await program.methods.proposeCollectionUpdateAuthority({
+        newAuthority: typo.publicKey,
+      }).accountsPartial({
+        authority: payer.publicKey,
+        collection: collection.publicKey,
+      }).signers([payer.payer]).rpc().then(log);
+
+      await program.methods.cancelCollectionUpdateAuthority().accountsPartial({
+        proposer: payer.publicKey,
+        collection: collection.publicKey,
+      }).signers([payer.payer]).rpc().then(log);
+
+      // the original authority can update the collection again
+      await program.methods.updateCollection({
+        name: null,
+        uri: "https://example.com/collection-v2.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        newUpdateAuthority: null,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+    })
+  })
+
+  describe("Covers moving assets between collections",()=>{
+    it("should move a standalone asset into a collection, to another collection and back out",async()=>{
+      const otherCollection = anchor.web3.Keypair.generate();
+      for (const target of [collection, otherCollection]) {
+        await program.methods.createCollection({
+          name: "My NFT Collection",
+          uri: "https://example.com/collection.json",
+        }).accountsPartial({
+          payer: payer.publicKey,
+          collection: target.publicKey,
+          updateAuthority: payer.publicKey,
+        }).signers([payer.payer, target]).rpc().then(log);
+      }
+
+      await program.methods.createNft({
+          name: "My NFT",
+        uri: "https://example.com/nft.json"
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        updateAuthority: payer.publicKey,
+        authority: payer.publicKey,
+        collection: null,
+        owner: payer.publicKey,
+      }).signers([payer.payer, asset]).rpc().then(log)
+
+      const moveNft = (from: anchor.web3.PublicKey | null, to: anchor.web3.PublicKey | null) =>
+        program.methods.moveNft({ newUpdateAuthority: null }).accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: from,
+          newCollection: to,
+          authority: payer.publicKey,
+          logWrapper: null,
+        }).signers([payer.payer]).rpc().then(log);
+
+      await moveNft(null, collection.publicKey);
+      await moveNft(collection.publicKey, otherCollection.publicKey);
+      await moveNft(otherCollection.publicKey, null);
+    })
+
+    it("should reject moving an asset into the collection it is already in",async()=>{
+      await program.methods.createCollection({
+        name: "My NFT Collection",
+        uri: "https://example.com/collection.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      await program.methods.createNft({
+        name: "My NFT",
+        uri: "https://example.com/nft.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        owner: payer.publicKey,
+        updateAuthority:null
+      }).signers([payer.payer, asset]).rpc().then(log);
+
+      try {
+        await program.methods.moveNft({ newUpdateAuthority: null }).accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: collection.publicKey,
+          newCollection: collection.publicKey,
+          authority: payer.publicKey,
+          logWrapper: null,
+        }).signers([payer.payer]).rpc()
+        assert.fail("the asset is already in the collection")
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "SameCollection")
+      }
+    })
+  })
+
+  describe("Covers collections controlled by a program PDA",()=>{
+    const programCollectionPdas = (collectionKey: anchor.web3.PublicKey) => {
+      const [collectionConfig] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("collection_config"), collectionKey.toBuffer()],
+        program.programId
+      );
+      const [collectionAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("collection_authority"), collectionConfig.toBuffer()],
+        program.programId
+      );
+      return { collectionConfig, collectionAuthority };
+    };
+
+    const mintRecordPda = (collectionKey: anchor.web3.PublicKey, minter: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("mint_record"), collectionKey.toBuffer(), minter.toBuffer()],
+        program.programId
+      )[0];
+
+    it("should create a collection owned by the program PDA and mint into it",async()=>{
+      const { collectionConfig, collectionAuthority } = programCollectionPdas(collection.publicKey);
+
+      await program.methods.createProgramCollection({
+        name: "My Program Collection",
+        uri: "https://example.com/collection.json",
+        plugins: [],
+        externalPluginAdapters: [],
+      }).accountsPartial({
+        payer: payer.publicKey,
+        authority: payer.publicKey,
+        collection: collection.publicKey,
+        collectionConfig,
+        collectionAuthority,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      await program.methods.createNft({
+        name: "My NFT",
+        uri: "https://example.com/nft.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        owner: payer.publicKey,
+        updateAuthority: null,
+        collectionConfig,
+        collectionAuthority,
+        mintRecord: mintRecordPda(collection.publicKey, payer.publicKey),
+      }).signers([payer.payer, asset]).rpc().then(log);
+    })
+
+    it("should rename a program collection with the collection authority PDA",async()=>{
+      const { collectionConfig, collectionAuthority } = programCollectionPdas(collection.publicKey);
+
+      await program.methods.createProgramCollection({
+        name: "My Program Collection",
+        uri: "https://example.com/collection.json",
+        plugins: [],
+        externalPluginAdapters: [],
+      }).accountsPartial({
+        payer: payer.publicKey,
+        authority: payer.publicKey,
+        collection: collection.publicKey,
+        collectionConfig,
+        collectionAuthority,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      await program.methods.updateCollection({
+        name: "Renamed Program Collection",
+        uri: null,
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        collectionConfig,
+        collectionAuthority,
+        newUpdateAuthority: null,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+    })
+
+    it("should only let the config authority mint into a program collection",async()=>{
+      const { collectionConfig, collectionAuthority } = programCollectionPdas(collection.publicKey);
+
+      await program.methods.createProgramCollection({
+        name: "My Program Collection",
+        uri: "https://example.com/collection.json",
+        plugins: [],
+        externalPluginAdapters: [],
+      }).accountsPartial({
+        payer: payer.publicKey,
+        authority: payer.publicKey,
+        collection: collection.publicKey,
+        collectionConfig,
+        collectionAuthority,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      const stranger = anchor.web3.Keypair.generate();
+      try {
+        await program.methods.createNft({
+          name: "My NFT",
+          uri: "https://example.com/nft.json",
+        }).accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: collection.publicKey,
+          authority: stranger.publicKey,
+          owner: payer.publicKey,
+          updateAuthority: null,
+          collectionConfig,
+          collectionAuthority,
+          mintRecord: mintRecordPda(collection.publicKey, payer.publicKey),
+        }).signers([payer.payer, asset, stranger]).rpc()
+        assert.fail("only the config authority can mint");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "UnauthorizedSigner");
+      }
+    })
+
+    const publicPhase = (maxPerWallet: number | null) => ({
+      startTime: new anchor.BN(0),
+      endTime: null,
+      price: new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 10),
+      maxPerWallet,
+      rule: { public: {} },
+    });
+
+    const createMintConfig = async (collectionKey: anchor.web3.PublicKey, treasury: anchor.web3.PublicKey, phases: any[]) => {
+      const { collectionConfig, collectionAuthority } = programCollectionPdas(collectionKey);
+      const [mintConfig] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("mint_config"), collectionKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods.createProgramCollection({
+        name: "My Drop",
+        uri: "https://example.com/collection.json",
+        plugins: [],
+        externalPluginAdapters: [],
+      }).accountsPartial({
+        payer: payer.publicKey,
+        authority: payer.publicKey,
+        collection: collectionKey,
+        collectionConfig,
+        collectionAuthority,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      await program.methods.initializeMintConfig({
+        treasury,
+        maxSupply: 100,
+        nameTemplate: "My Drop #{id}",
+        uriTemplate: "https://example.com/drop/{id}.json",
+        phases,
+      }).accountsPartial({
+        payer: payer.publicKey,
+        authority: payer.publicKey,
+        collectionConfig,
+        mintConfig,
+      }).signers([payer.payer]).rpc().then(log);
+
+      return { collectionConfig, collectionAuthority, mintConfig };
+    };
+
+    it("should let anyone buy an asset from a mint config",async()=>{
+      const treasury = anchor.web3.Keypair.generate().publicKey;
+      const { collectionConfig, collectionAuthority, mintConfig } = await createMintConfig(collection.publicKey, treasury, [publicPhase(null)]);
+
+      await program.methods.mintFromConfig({ allowlist: null, collectionAllowlist: null }).accountsPartial({
+        buyer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        collectionConfig,
+        collectionAuthority,
+        mintConfig,
+        treasury,
+        keyAsset: null,
+        keyAssetRecord: null,
+      }).signers([payer.payer, asset]).rpc().then(log);
+
+      assert.equal(await connection.getBalance(treasury), anchor.web3.LAMPORTS_PER_SOL / 10);
+      const config = await program.account.mintConfig.fetch(mintConfig);
+      assert.equal(config.minted, 1);
+    })
+
+    it("should stop a wallet at the mint limit of the config",async()=>{
+      const treasury = anchor.web3.Keypair.generate().publicKey;
+      const { collectionConfig, collectionAuthority, mintConfig } = await createMintConfig(collection.publicKey, treasury, [publicPhase(1)]);
+
+      await program.methods.mintFromConfig({ allowlist: null, collectionAllowlist: null }).accountsPartial({
+        buyer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        collectionConfig,
+        collectionAuthority,
+        mintConfig,
+        treasury,
+        keyAsset: null,
+        keyAssetRecord: null,
+      }).signers([payer.payer, asset]).rpc().then(log);
+
+      const secondAsset = anchor.web3.Keypair.generate();
+      try {
+        await program.methods.mintFromConfig({ allowlist: null, collectionAllowlist: null }).accountsPartial({
+          buyer: payer.publicKey,
+          asset: secondAsset.publicKey,
+          collection: collection.publicKey,
+          collectionConfig,
+          collectionAuthority,
+          mintConfig,
+          treasury,
+          keyAsset: null,
+          keyAssetRecord: null,
+        }).signers([payer.payer, secondAsset]).rpc()
+        assert.fail("the wallet already minted its only asset");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "WalletMintLimitReached");
+      }
+    })
+
+    it("should resolve the active mint phase from the clock",async()=>{
+      const treasury = anchor.web3.Keypair.generate().publicKey;
+      const now = Math.floor(Date.now() / 1000);
+      const { collectionConfig, collectionAuthority, mintConfig } = await createMintConfig(collection.publicKey, treasury, [
+        { ...publicPhase(null), startTime: new anchor.BN(now + 3600) },
+      ]);
+
+      try {
+        await program.methods.mintFromConfig({ allowlist: null, collectionAllowlist: null }).accountsPartial({
+          buyer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: collection.publicKey,
+          collectionConfig,
+          collectionAuthority,
+          mintConfig,
+          treasury,
+          keyAsset: null,
+          keyAssetRecord: null,
+        }).signers([payer.payer, asset]).rpc()
+        assert.fail("the only phase starts in an hour");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "MintNotStarted");
+      }
+    })
+
+    it("should only let holders of the key collection mint, once per key asset",async()=>{
+      const keyCollection = anchor.web3.Keypair.generate();
+      await program.methods.createCollection({
+        name: "Key Collection",
+        uri: "https://example.com/collection.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: keyCollection.publicKey,
+        updateAuthority: payer.publicKey,
+      }).signers([payer.payer, keyCollection]).rpc().then(log);
+
+      const keyAsset = anchor.web3.Keypair.generate();
+      await program.methods.createNft({
+        name: "My Key",
+        uri: "https://example.com/nft.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: keyAsset.publicKey,
+        collection: keyCollection.publicKey,
+        authority: payer.publicKey,
+        owner: null,
+        updateAuthority: null,
+        collectionConfig: null,
+        collectionAuthority: null,
+        mintRecord: null,
+      }).signers([payer.payer, keyAsset]).rpc().then(log);
+
+      const treasury = anchor.web3.Keypair.generate().publicKey;
+      const { collectionConfig, collectionAuthority, mintConfig } = await createMintConfig(collection.publicKey, treasury, [
+        { ...publicPhase(null), rule: { holders: { collection: keyCollection.publicKey, singleUse: true } } },
+      ]);
+
+      const [keyAssetRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("key_asset_record"), collection.publicKey.toBuffer(), keyAsset.publicKey.toBuffer()],
+        program.programId
+      );
+
+      const mint = (newAsset: anchor.web3.Keypair, key: anchor.web3.PublicKey | null) =>
+        program.methods.mintFromConfig({ allowlist: null, collectionAllowlist: null }).accountsPartial({
+          buyer: payer.publicKey,
+          asset: newAsset.publicKey,
+          collection: collection.publicKey,
+          collectionConfig,
+          collectionAuthority,
+          mintConfig,
+          treasury,
+          keyAsset: key,
+          keyAssetRecord: key ? keyAssetRecord : null,
+        }).signers([payer.payer, newAsset]).rpc();
+
+      try {
+        await mint(asset, null);
+        assert.fail("a key asset is required");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "NotAHolder");
+      }
+
+      await mint(anchor.web3.Keypair.generate(), keyAsset.publicKey).then(log);
+
+      try {
+        await mint(anchor.web3.Keypair.generate(), keyAsset.publicKey);
+        assert.fail("the key asset was already used");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "KeyAssetAlreadyUsed");
+      }
+    })
+
+    it("should only let the authority edit mint phases that have not started",async()=>{
+      const treasury = anchor.web3.Keypair.generate().publicKey;
+      const now = Math.floor(Date.now() / 1000);
+      const started = { ...publicPhase(null), endTime: new anchor.BN(now + 3600) };
+      const upcoming = { ...publicPhase(1), startTime: new anchor.BN(now + 3600) };
+      const { collectionConfig, mintConfig } = await createMintConfig(collection.publicKey, treasury, [started, upcoming]);
+
+      await program.methods.updateMintPhases({
+        phases: [started, { ...upcoming, price: new anchor.BN(0) }],
+      }).accountsPartial({
+        authority: payer.publicKey,
+        collectionConfig,
+        mintConfig,
+      }).signers([payer.payer]).rpc().then(log);
+
+      try {
+        await program.methods.updateMintPhases({
+          phases: [{ ...started, price: new anchor.BN(0) }, upcoming],
+        }).accountsPartial({
+          authority: payer.publicKey,
+          collectionConfig,
+          mintConfig,
+        }).signers([payer.payer]).rpc()
+        assert.fail("the first phase has already started");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "MintPhaseStarted");
+      }
+    })
+
+    it("should require the collection allowlist when buying from a mint config",async()=>{
+      const treasury = anchor.web3.Keypair.generate().publicKey;
+      const { collectionConfig, collectionAuthority, mintConfig } = await createMintConfig(collection.publicKey, treasury, [publicPhase(null)]);
+
+      // a single leaf tree, its root is the leaf itself
+      const allocationBytes = Buffer.alloc(4);
+      allocationBytes.writeUInt32LE(1);
+      const root = createHash("sha256").update(Buffer.from([0])).update(payer.publicKey.toBuffer()).update(allocationBytes).digest();
+      await program.methods.setCollectionAllowlistRoot({ root: Array.from(root) }).accountsPartial({
+        authority: payer.publicKey,
+        collectionConfig,
+      }).signers([payer.payer]).rpc().then(log);
+
+      const mint = (allowlist: any) => program.methods.mintFromConfig({ allowlist: null, collectionAllowlist: allowlist }).accountsPartial({
+        buyer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        collectionConfig,
+        collectionAuthority,
+        mintConfig,
+        treasury,
+        keyAsset: null,
+        keyAssetRecord: null,
+      }).signers([payer.payer, asset]).rpc();
+
+      try {
+        await mint(null);
+        assert.fail("the collection has an allowlist");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "AllowlistProofRequired");
+      }
+
+      await mint({ allocation: 1, proof: [] }).then(log);
+    })
+
+    it("should let the authority change the treasury and templates of a mint config",async()=>{
+      const treasury = anchor.web3.Keypair.generate().publicKey;
+      const { collectionConfig, collectionAuthority, mintConfig } = await createMintConfig(collection.publicKey, treasury, [publicPhase(null)]);
+      const newTreasury = anchor.web3.Keypair.generate().publicKey;
+
+      try {
+        await program.methods.updateMintConfig({
+          treasury: null,
+          nameTemplate: "A name template that is far too long #{id}",
+          uriTemplate: null,
+        }).accountsPartial({
+          authority: payer.publicKey,
+          collectionConfig,
+          mintConfig,
+        }).signers([payer.payer]).rpc()
+        assert.fail("the name template doesn't fit in the mint config");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "NameTooLong");
+      }
+
+      await program.methods.updateMintConfig({
+        treasury: newTreasury,
+        nameTemplate: "My Drop v2 #{id}",
+        uriTemplate: null,
+      }).accountsPartial({
+        authority: payer.publicKey,
+        collectionConfig,
+        mintConfig,
+      }).signers([payer.payer]).rpc().then(log);
+
+      await program.methods.mintFromConfig({ allowlist: null, collectionAllowlist: null }).accountsPartial({
+        buyer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        collectionConfig,
+        collectionAuthority,
+        mintConfig,
+        treasury: newTreasury,
+        keyAsset: null,
+        keyAssetRecord: null,
+      }).signers([payer.payer, asset]).rpc().then(log);
+
+      assert.equal(await connection.getBalance(newTreasury), anchor.web3.LAMPORTS_PER_SOL / 10);
+      const config = await program.account.mintConfig.fetch(mintConfig);
+      assert.equal(config.nameTemplate, "My Drop v2 #{id}");
+      assert.equal(config.uriTemplate, "https://example.com/drop/{id}.json");
+    })
+
+    it("should enforce the per-wallet limit across mints and reset it in a new phase",async()=>{
+      const { collectionConfig, collectionAuthority } = programCollectionPdas(collection.publicKey);
+      const fan = anchor.web3.Keypair.generate().publicKey;
+      const mintRecord = mintRecordPda(collection.publicKey, fan);
+
+      await program.methods.createProgramCollection({
+        name: "My Program Collection",
+        uri: "https://example.com/collection.json",
+        plugins: [],
+        externalPluginAdapters: [],
+      }).accountsPartial({
+        payer: payer.publicKey,
+        authority: payer.publicKey,
+        collection: collection.publicKey,
+        collectionConfig,
+        collectionAuthority,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      await program.methods.setCollectionMintLimit({ maxPerWallet: 1 }).accountsPartial({
+        authority: payer.publicKey,
+        collectionConfig,
+      }).signers([payer.payer]).rpc().then(log);
+
+      const mintToFan = (newAsset: anchor.web3.Keypair) => program.methods.createNft({
+        name: "My NFT",
+        uri: "https://example.com/nft.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: newAsset.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        owner: fan,
+        updateAuthority: null,
+        collectionConfig,
+        collectionAuthority,
+        mintRecord,
+      }).signers([payer.payer, newAsset]).rpc();
+
+      await mintToFan(asset).then(log);
+
+      try {
+        await mintToFan(anchor.web3.Keypair.generate());
+        assert.fail("the fan already received an asset in this phase");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "WalletMintLimitReached");
+      }
+
+      await program.methods.startCollectionMintPhase().accountsPartial({
+        authority: payer.publicKey,
+        collectionConfig,
+      }).signers([payer.payer]).rpc().then(log);
+
+      await mintToFan(anchor.web3.Keypair.generate()).then(log);
+      const record = await program.account.mintRecord.fetch(mintRecord);
+      assert.equal(record.phase, 1);
+      assert.equal(record.minted, 1);
+    })
+
+    it("should only mint to allowlisted wallets once a root is set",async()=>{
+      const { collectionConfig, collectionAuthority } = programCollectionPdas(collection.publicKey);
+      const fan = anchor.web3.Keypair.generate();
+      const stranger = anchor.web3.Keypair.generate();
+
+      // leaves are sha256(0x00 || wallet || allocation as u32 LE), nodes sha256(0x01 || sorted children)
+      const leaf = (wallet: anchor.web3.PublicKey, allocation: number) => {
+        const allocationBytes = Buffer.alloc(4);
+        allocationBytes.writeUInt32LE(allocation);
+        return createHash("sha256").update(Buffer.from([0])).update(wallet.toBuffer()).update(allocationBytes).digest();
+      };
+      const fanLeaf = leaf(fan.publicKey, 2);
+      const otherLeaf = leaf(anchor.web3.Keypair.generate().publicKey, 1);
+      const [left, right] = Buffer.compare(fanLeaf, otherLeaf) <= 0 ? [fanLeaf, otherLeaf] : [otherLeaf, fanLeaf];
+      const root = createHash("sha256").update(Buffer.from([1])).update(left).update(right).digest();
+
+      await program.methods.createProgramCollection({
+        name: "My Program Collection",
+        uri: "https://example.com/collection.json",
+        plugins: [],
+        externalPluginAdapters: [],
+      }).accountsPartial({
+        payer: payer.publicKey,
+        authority: payer.publicKey,
+        collection: collection.publicKey,
+        collectionConfig,
+        collectionAuthority,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      await program.methods.setCollectionAllowlistRoot({ root: Array.from(root) }).accountsPartial({
+        authority: payer.publicKey,
+        collectionConfig,
+      }).signers([payer.payer]).rpc().then(log);
+
+      const accountsFor = (newAsset: anchor.web3.Keypair, owner: anchor.web3.PublicKey, authority: anchor.web3.PublicKey | null) => ({
+        payer: payer.publicKey,
+        asset: newAsset.publicKey,
+        collection: collection.publicKey,
+        authority,
+        owner,
+        updateAuthority: null,
+        collectionConfig,
+        collectionAuthority,
+        mintRecord: mintRecordPda(collection.publicKey, owner),
+      });
+
+      // the listed wallet mints for itself without the config authority,
+      // `owner` is not a signer in the IDL so it is flagged by hand
+      const mintFromAllowlist = async (newAsset: anchor.web3.Keypair, owner: anchor.web3.Keypair, ownerSigns = true) => {
+        const ix = await program.methods.createNftFromAllowlist(
+          {
+            name: "My NFT",
+            uri: "https://example.com/nft.json",
+            plugins: [{ plugin: { permanentBurnDelegate: {} }, authority: { updateAuthority: {} } }],
+          },
+          { allocation: 2, proof: [Array.from(otherLeaf)] },
+        ).accountsPartial(accountsFor(newAsset, owner.publicKey, null)).instruction();
+        if (!ownerSigns) {
+          return provider.sendAndConfirm(new anchor.web3.Transaction().add(ix), [payer.payer, newAsset]);
+        }
+        ix.keys.filter((key) => key.pubkey.equals(owner.publicKey)).forEach((key) => { key.isSigner = true; });
+        return provider.sendAndConfirm(new anchor.web3.Transaction().add(ix), [payer.payer, newAsset, owner]);
+      };
+      const errorCode = (err: any) => anchor.AnchorError.parse(err.logs)?.error.errorCode.code;
+
+      await mintFromAllowlist(asset, fan).then(log);
+
+      try {
+        await mintFromAllowlist(anchor.web3.Keypair.generate(), fan, false);
+        assert.fail("the allowlisted owner has to sign");
+      } catch (err) {
+        assert.equal(errorCode(err), "UnauthorizedSigner");
+      }
+
+      try {
+        await mintFromAllowlist(anchor.web3.Keypair.generate(), stranger);
+        assert.fail("the stranger is not on the allowlist");
+      } catch (err) {
+        assert.equal(errorCode(err), "InvalidAllowlistProof");
+      }
+
+      // the config authority still needs a proof for the owner it mints to
+      const strangerAsset = anchor.web3.Keypair.generate();
+      try {
+        await program.methods.createNft({ name: "My NFT", uri: "https://example.com/nft.json" })
+          .accountsPartial(accountsFor(strangerAsset, fan.publicKey, payer.publicKey)).signers([payer.payer, strangerAsset]).rpc()
+        assert.fail("minting without a proof should fail");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "AllowlistProofRequired");
+      }
+    })
+  })
+
+  describe("Covers escrowless staking",()=>{
+    const stakingPdas = (collectionKey: anchor.web3.PublicKey, assetKey: anchor.web3.PublicKey) => {
+      const [stakePool] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stake_pool"), collectionKey.toBuffer()],
+        program.programId
+      );
+      const [stakeRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("stake_record"), assetKey.toBuffer()],
+        program.programId
+      );
+      return { stakePool, stakeRecord };
+    };
+
+    // `plugins` are attached to the asset, e.g. the attributes selecting its reward multiplier
+    const createStakeableAsset = async (plugins: any[] = []) => {
+      await program.methods.createCollection({
+        name: "My Staking Collection",
+        uri: "https://example.com/collection.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      await program.methods.createNftWithPlugins({
+        name: "My NFT",
+        uri: "https://example.com/nft.json",
+        plugins,
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        owner: null,
+        updateAuthority: null,
+        collectionConfig: null,
+        collectionAuthority: null,
+        mintRecord: null,
+      }).signers([payer.payer, asset]).rpc().then(log);
+
+      const { stakePool, stakeRecord } = stakingPdas(collection.publicKey, asset.publicKey);
+      await program.methods.initializeStakePool({ rewardRate: new anchor.BN(10) }).accountsPartial({
+        authority: payer.publicKey,
+        collection: collection.publicKey,
+        stakePool,
+      }).signers([payer.payer]).rpc().then(log);
+
+      return { stakePool, stakeRecord };
+    };
+
+    // Pools without a reward vault keep their rewards owed until one is created
+    const noRewardAccounts = {
+      rewardMint: null,
+      rewardVault: null,
+      ownerRewardAccount: null,
+      tokenProgram: null,
+    };
+
+    // Creates the reward vault of `stakePool` and funds it with `funding` tokens
+    const createRewardVault = async (stakePool: anchor.web3.PublicKey, funding: number, tokenProgram = TOKEN_PROGRAM_ID) => {
+      const { mint: rewardMint, payerTokenAccount } = await createMint(1_000_000, tokenProgram);
+      const [rewardVault] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("reward_vault"), stakePool.toBuffer()],
+        program.programId
+      );
+
+      await program.methods.initializeRewardVault().accountsPartial({
+        authority: payer.publicKey,
+        stakePool,
+        rewardMint,
+        rewardVault,
+        tokenProgram,
+      }).signers([payer.payer]).rpc().then(log);
+
+      if (funding > 0) {
+        await program.methods.fundRewardVault({ amount: new anchor.BN(funding) }).accountsPartial({
+          funder: payer.publicKey,
+          stakePool,
+          rewardMint,
+          rewardVault,
+          funderTokenAccount: payerTokenAccount,
+          tokenProgram,
+        }).signers([payer.payer]).rpc().then(log);
+      }
+      return { rewardMint, rewardVault, payerTokenAccount };
+    };
+
+    const pendingRewardsPda = (stakePool: anchor.web3.PublicKey, owner: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("pending_rewards"), stakePool.toBuffer(), owner.toBuffer()],
+        program.programId
+      )[0];
+
+    it("should freeze a staked asset in the owner's wallet and thaw it on unstake",async()=>{
+      const { stakePool, stakeRecord } = await createStakeableAsset();
+      const stakingAccounts = {
+        owner: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        stakePool,
+        stakeRecord,
+        logWrapper: null,
+      };
+
+      await program.methods.stake().accountsPartial(stakingAccounts).signers([payer.payer]).rpc().then(log);
+
+      let transferred = false;
+      try {
+        await program.methods.transferNft().accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: collection.publicKey,
+          newOwner: anchor.web3.Keypair.generate().publicKey,
+        }).signers([payer.payer]).rpc();
+        transferred = true;
+      } catch (err) {
+        console.log(err);
+      }
+      assert.isFalse(transferred, "a staked asset must stay frozen");
+
+      await program.methods.claimStakingRewards().accountsPartial({
+        owner: payer.publicKey,
+        stakePool,
+        stakeRecord,
+        ...noRewardAccounts,
+      }).signers([payer.payer]).rpc().then(log);
+
+      // the pool has no vault, so the rewards accrued since the claim stay pending
+      await program.methods.unstake().accountsPartial({
+        ...stakingAccounts,
+        ...noRewardAccounts,
+        pendingRewards: pendingRewardsPda(stakePool, payer.publicKey),
+      }).signers([payer.payer]).rpc().then(log);
+
+      const pool = await program.account.stakePool.fetch(stakePool);
+      assert.equal(pool.totalStaked, 0);
+      assert.isNull(await connection.getAccountInfo(stakeRecord));
+
+      await program.methods.transferNft().accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        newOwner: anchor.web3.Keypair.generate().publicKey,
+      }).signers([payer.payer]).rpc().then(log);
+    })
+
+    it("should only let the owner unstake",async()=>{
+      const { stakePool, stakeRecord } = await createStakeableAsset();
+
+      await program.methods.stake().accountsPartial({
+        owner: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        stakePool,
+        stakeRecord,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+
+      const stranger = anchor.web3.Keypair.generate();
+      try {
+        await program.methods.unstake().accountsPartial({
+          owner: stranger.publicKey,
+          asset: asset.publicKey,
+          collection: collection.publicKey,
+          stakePool,
+          stakeRecord,
+          pendingRewards: null,
+          logWrapper: null,
+          ...noRewardAccounts,
+        }).signers([stranger]).rpc()
+        assert.fail("only the owner can unstake");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "UnauthorizedSigner");
+      }
+    })
+
+    it("should pay staking rewards in tokens from the pool vault",async()=>{
+      const { stakePool, stakeRecord } = await createStakeableAsset();
+      const { mint: rewardMint, payerTokenAccount } = await createMint(1_000_000);
+      const [rewardVault] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("reward_vault"), stakePool.toBuffer()],
+        program.programId
+      );
+
+      await program.methods.initializeRewardVault().accountsPartial({
+        authority: payer.publicKey,
+        stakePool,
+        rewardMint,
+        rewardVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      }).signers([payer.payer]).rpc().then(log);
+
+      await program.methods.fundRewardVault({ amount: new anchor.BN(500_000) }).accountsPartial({
+        funder: payer.publicKey,
+        stakePool,
+        rewardMint,
+        rewardVault,
+        funderTokenAccount: payerTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      }).signers([payer.payer]).rpc().then(log);
+
+      await program.methods.stake().accountsPartial({
+        owner: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        stakePool,
+        stakeRecord,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      // The pool has a vault, so claiming needs the token accounts
+      try {
+        await program.methods.claimStakingRewards().accountsPartial({
+          owner: payer.publicKey,
+          stakePool,
+          stakeRecord,
+          ...noRewardAccounts,
+        }).signers([payer.payer]).rpc();
+        assert.fail("claiming should need the reward accounts");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "RewardAccountsRequired");
+      }
+
+      const before = Number((await connection.getTokenAccountBalance(payerTokenAccount)).value.amount);
+      await program.methods.claimStakingRewards().accountsPartial({
+        owner: payer.publicKey,
+        stakePool,
+        stakeRecord,
+        rewardMint,
+        rewardVault,
+        ownerRewardAccount: payerTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      }).signers([payer.payer]).rpc().then(log);
+      const after = Number((await connection.getTokenAccountBalance(payerTokenAccount)).value.amount);
+
+      const record = await program.account.stakeRecord.fetch(stakeRecord);
+      assert.isAbove(after - before, 0);
+      assert.equal(record.rewardsClaimed.toNumber(), after - before);
+      assert.equal(record.multiplierBps, 10_000);
+
+      // Changing the rate checkpoints what was earned at the old one
+      await program.methods.setRewardRate({ rewardRate: new anchor.BN(0) }).accountsPartial({
+        authority: payer.publicKey,
+        stakePool,
+      }).signers([payer.payer]).rpc().then(log);
+      const pool = await program.account.stakePool.fetch(stakePool);
+      assert.equal(pool.rewardRate.toNumber(), 0);
+      assert.isTrue(pool.rewardPerAsset.gte(record.rewardDebt));
+    })
+
+    it("should pay staking rewards in Token-2022 tokens",async()=>{
+      const { stakePool, stakeRecord } = await createStakeableAsset();
+      const { rewardMint, rewardVault, payerTokenAccount } = await createRewardVault(stakePool, 500_000, TOKEN_2022_PROGRAM_ID);
+
+      await program.methods.stake().accountsPartial({
+        owner: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        stakePool,
+        stakeRecord,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      const before = Number((await connection.getTokenAccountBalance(payerTokenAccount)).value.amount);
+      await program.methods.claimStakingRewards().accountsPartial({
+        owner: payer.publicKey,
+        stakePool,
+        stakeRecord,
+        rewardMint,
+        rewardVault,
+        ownerRewardAccount: payerTokenAccount,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }).signers([payer.payer]).rpc().then(log);
+      const after = Number((await connection.getTokenAccountBalance(payerTokenAccount)).value.amount);
+
+      const record = await program.account.stakeRecord.fetch(stakeRecord);
+      assert.isAbove(after - before, 0);
+      assert.equal(record.rewardsClaimed.toNumber(), after - before);
+    })
+
+    it("should keep the rewards of a pool without a vault owed",async()=>{
+      const { stakePool, stakeRecord } = await createStakeableAsset();
+
+      await program.methods.stake().accountsPartial({
+        owner: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        stakePool,
+        stakeRecord,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      await program.methods.claimStakingRewards().accountsPartial({
+        owner: payer.publicKey,
+        stakePool,
+        stakeRecord,
+        ...noRewardAccounts,
+      }).signers([payer.payer]).rpc().then(log);
+
+      const record = await program.account.stakeRecord.fetch(stakeRecord);
+      assert.isAbove(record.unpaidRewards.toNumber(), 0);
+      assert.equal(record.rewardsClaimed.toNumber(), 0);
+      const pool = await program.account.stakePool.fetch(stakePool);
+      assert.equal(pool.totalRewardsClaimed.toNumber(), 0);
+    })
+
+    it("should keep the rewards the vault can't pay claimable after unstaking",async()=>{
+      const { stakePool, stakeRecord } = await createStakeableAsset();
+      const { rewardMint, rewardVault, payerTokenAccount } = await createRewardVault(stakePool, 0);
+      const pendingRewards = pendingRewardsPda(stakePool, payer.publicKey);
+      const rewardAccounts = {
+        rewardMint,
+        rewardVault,
+        ownerRewardAccount: payerTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+
+      await program.methods.stake().accountsPartial({
+        owner: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        stakePool,
+        stakeRecord,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      // The vault is empty, so nothing is paid but unstaking still works
+      await program.methods.unstake().accountsPartial({
+        owner: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        stakePool,
+        stakeRecord,
+        pendingRewards,
+        logWrapper: null,
+        ...rewardAccounts,
+      }).signers([payer.payer]).rpc().then(log);
+      assert.isNull(await connection.getAccountInfo(stakeRecord));
+
+      const owed = (await program.account.pendingRewards.fetch(pendingRewards)).amount.toNumber();
+      assert.isAbove(owed, 0);
+
+      await program.methods.fundRewardVault({ amount: new anchor.BN(500_000) }).accountsPartial({
+        funder: payer.publicKey,
+        stakePool,
+        rewardMint,
+        rewardVault,
+        funderTokenAccount: payerTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      }).signers([payer.payer]).rpc().then(log);
+
+      const before = Number((await connection.getTokenAccountBalance(payerTokenAccount)).value.amount);
+      await program.methods.claimPendingRewards().accountsPartial({
+        owner: payer.publicKey,
+        stakePool,
+        pendingRewards,
+        ...rewardAccounts,
+      }).signers([payer.payer]).rpc().then(log);
+      const after = Number((await connection.getTokenAccountBalance(payerTokenAccount)).value.amount);
+
+      assert.equal(after - before, owed);
+      // everything is paid, so the account is closed and its rent refunded
+      assert.isNull(await connection.getAccountInfo(pendingRewards));
+      const pool = await program.account.stakePool.fetch(stakePool);
+      assert.equal(pool.totalRewardsClaimed.toNumber(), owed);
+    })
+
+    it("should only create the pending rewards when the vault can't pay them",async()=>{
+      const { stakePool, stakeRecord } = await createStakeableAsset();
+      const { rewardMint, rewardVault, payerTokenAccount } = await createRewardVault(stakePool, 500_000);
+      const stakingAccounts = {
+        owner: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        stakePool,
+        stakeRecord,
+        logWrapper: null,
+      };
+
+      await program.methods.stake().accountsPartial(stakingAccounts).signers([payer.payer]).rpc().then(log);
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      // the vault pays everything, so no pending rewards account is needed
+      await program.methods.unstake().accountsPartial({
+        ...stakingAccounts,
+        pendingRewards: null,
+        rewardMint,
+        rewardVault,
+        ownerRewardAccount: payerTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      }).signers([payer.payer]).rpc().then(log);
+
+      assert.isNull(await connection.getAccountInfo(stakeRecord));
+      assert.isNull(await connection.getAccountInfo(pendingRewardsPda(stakePool, payer.publicKey)));
+    })
+
+    it("should weight the rewards of an asset by the multiplier of its attribute",async()=>{
+      const { stakePool, stakeRecord } = await createStakeableAsset([{
+        plugin: { attributes: { attributeList: [{ key: "rarity", value: "legendary" }] } },
+        authority: null,
+      }]);
+
+      try {
+        await program.methods.setRewardMultipliers({
+          attribute: "rarity",
+          multipliers: [{ value: "a value that is longer than the limit", multiplierBps: 20_000 }],
+        }).accountsPartial({
+          authority: payer.publicKey,
+          stakePool,
+        }).signers([payer.payer]).rpc()
+        assert.fail("the attribute value is too long");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "RewardAttributeTooLong");
+      }
+
+      const stranger = anchor.web3.Keypair.generate();
+      try {
+        await program.methods.setRewardMultipliers({
+          attribute: "rarity",
+          multipliers: [{ value: "legendary", multiplierBps: 20_000 }],
+        }).accountsPartial({
+          authority: stranger.publicKey,
+          stakePool,
+        }).signers([stranger]).rpc()
+        assert.fail("only the pool authority can set multipliers");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "UnauthorizedSigner");
+      }
+
+      await program.methods.setRewardMultipliers({
+        attribute: "rarity",
+        multipliers: [
+          { value: "common", multiplierBps: 5_000 },
+          { value: "legendary", multiplierBps: 20_000 },
+        ],
+      }).accountsPartial({
+        authority: payer.publicKey,
+        stakePool,
+      }).signers([payer.payer]).rpc().then(log);
+
+      await program.methods.stake().accountsPartial({
+        owner: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        stakePool,
+        stakeRecord,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+
+      const record = await program.account.stakeRecord.fetch(stakeRecord);
+      assert.equal(record.multiplierBps, 20_000);
+    })
+  })
+
+  describe("Covers escrowless marketplace listings",()=>{
+    const creator = anchor.web3.Keypair.generate();
+    const price = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 10);
+
+    const listingPda = (assetKey: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("listing"), assetKey.toBuffer()],
+        program.programId
+      )[0];
+
+    // Lists an asset of a collection paying 5% royalties to `creator`
+    const createListedAsset = async () => {
+      await program.methods.createCollection({
+        name: "My Marketplace Collection",
+        uri: "https://example.com/collection.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      await program.methods.addRoyaltiesPluginToCollection({
+        basisPoints: 500,
+        creators: [{ address: creator.publicKey, percentage: 100 }],
+        ruleSet: { none: {} },
+        delegate: null,
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+
+      await program.methods.createNft({
+        name: "My NFT",
+        uri: "https://example.com/nft.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        owner: null,
+        updateAuthority: null,
+        collectionConfig: null,
+        collectionAuthority: null,
+        mintRecord: null,
+      }).signers([payer.payer, asset]).rpc().then(log);
+
+      const listing = listingPda(asset.publicKey);
+      await program.methods.list({ price, expiry: null }).accountsPartial({
+        seller: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        listing,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+      return listing;
+    };
+
+    const fundedBuyer = async () => {
+      const buyer = anchor.web3.Keypair.generate();
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: buyer.publicKey,
+          lamports: anchor.web3.LAMPORTS_PER_SOL / 2,
+        })
+      ));
+      return buyer;
+    };
+
+    it("should pay the seller and creators and hand the asset to the buyer",async()=>{
+      const listing = await createListedAsset();
+      const buyer = await fundedBuyer();
+
+      await program.methods.buy().accountsPartial({
+        buyer: buyer.publicKey,
+        seller: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        listing,
+        logWrapper: null,
+      }).remainingAccounts([
+        { pubkey: creator.publicKey, isSigner: false, isWritable: true },
+      ]).signers([buyer]).rpc().then(log);
+
+      assert.equal(await connection.getBalance(creator.publicKey), price.toNumber() * 500 / 10_000);
+      assert.isNull(await connection.getAccountInfo(listing));
+
+      // the buyer owns a thawed asset
+      await program.methods.transferNft().accountsPartial({
+        payer: buyer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        authority: buyer.publicKey,
+        newOwner: anchor.web3.Keypair.generate().publicKey,
+      }).signers([buyer]).rpc().then(log);
+    })
+
+    it("should require the creator accounts of the royalties",async()=>{
+      const listing = await createListedAsset();
+      const buyer = await fundedBuyer();
+
+      try {
+        await program.methods.buy().accountsPartial({
+          buyer: buyer.publicKey,
+          seller: payer.publicKey,
+          asset: asset.publicKey,
+          collection: collection.publicKey,
+          listing,
+          logWrapper: null,
+        }).signers([buyer]).rpc();
+        assert.fail("royalties must be paid");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "CreatorAccountMismatch");
+      }
+    })
+
+    it("should keep a listed asset frozen until it is delisted",async()=>{
+      const listing = await createListedAsset();
+
+      let transferred = false;
+      try {
+        await program.methods.transferNft().accountsPartial({
+          payer: payer.publicKey,
+          asset: asset.publicKey,
+          collection: collection.publicKey,
+          newOwner: anchor.web3.Keypair.generate().publicKey,
+        }).signers([payer.payer]).rpc();
+        transferred = true;
+      } catch (err) {
+        console.log(err);
+      }
+      assert.isFalse(transferred, "a listed asset must stay frozen");
+
+      await program.methods.delist().accountsPartial({
+        seller: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        listing,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+      assert.isNull(await connection.getAccountInfo(listing));
+
+      await program.methods.transferNft().accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        newOwner: anchor.web3.Keypair.generate().publicKey,
+      }).signers([payer.payer]).rpc().then(log);
+    })
+  })
+
+  describe("Covers royalty settlement",()=>{
+    const creators = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+
+    // An asset paying 10% royalties split 60/40
+    const createAssetWithRoyalties = async () => {
+      await program.methods.createNft({
+        name: "My NFT",
+        uri: "https://example.com/nft.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority: payer.publicKey,
+        owner: null,
+        updateAuthority: null,
+        collectionConfig: null,
+        collectionAuthority: null,
+        mintRecord: null,
+      }).signers([payer.payer, asset]).rpc().then(log);
+
+      await program.methods.addRoyaltiesPlugin({
+        basisPoints: 1_000,
+        creators: [
+          { address: creators[0].publicKey, percentage: 60 },
+          { address: creators[1].publicKey, percentage: 40 },
+        ],
+        ruleSet: { none: {} },
+        delegate: null,
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: null,
+        authority: payer.publicKey,
+      }).signers([payer.payer]).rpc().then(log);
+    };
+
+    const settlementAccounts = () => ({
+      payer: payer.publicKey,
+      asset: asset.publicKey,
+      collection: null,
+      mint: null,
+      payerTokenAccount: null,
+      tokenProgram: null,
+    });
+
+    it("should pay every creator their share of the sale in lamports",async()=>{
+      await createAssetWithRoyalties();
+      const before = await Promise.all(creators.map((creator) => connection.getBalance(creator.publicKey)));
+
+      await program.methods.settleRoyalties({ price: new anchor.BN(anchor.web3.LAMPORTS_PER_SOL) })
+        .accountsPartial(settlementAccounts())
+        .remainingAccounts(creators.map((creator) => ({ pubkey: creator.publicKey, isSigner: false, isWritable: true })))
+        .signers([payer.payer]).rpc().then(log);
+
+      const after = await Promise.all(creators.map((creator) => connection.getBalance(creator.publicKey)));
+      assert.equal(after[0] - before[0], anchor.web3.LAMPORTS_PER_SOL * 0.06);
+      assert.equal(after[1] - before[1], anchor.web3.LAMPORTS_PER_SOL * 0.04);
+    })
+
+    it("should reject creator accounts out of order",async()=>{
+      await createAssetWithRoyalties();
+
+      try {
+        await program.methods.settleRoyalties({ price: new anchor.BN(anchor.web3.LAMPORTS_PER_SOL) })
+          .accountsPartial(settlementAccounts())
+          .remainingAccounts([...creators].reverse().map((creator) => ({ pubkey: creator.publicKey, isSigner: false, isWritable: true })))
+          .signers([payer.payer]).rpc();
+        assert.fail("creators must match the royalties");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "CreatorAccountMismatch");
+      }
+    })
+
+    it("should pay every creator their share of the sale in an SPL token",async()=>{
+      await createAssetWithRoyalties();
+      const { mint, payerTokenAccount } = await createMint(1_000_000);
+      const creatorTokenAccounts = creators.map((creator) => associatedTokenAddress(creator.publicKey, mint));
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(
+        ...creators.map((creator) => createTokenAccountInstruction(creator.publicKey, mint)),
+      )).then(log);
+
+      await program.methods.settleRoyalties({ price: new anchor.BN(1_000_000) })
+        .accountsPartial({
+          ...settlementAccounts(),
+          mint,
+          payerTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(creatorTokenAccounts.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+        .signers([payer.payer]).rpc().then(log);
+
+      const balances = await Promise.all(creatorTokenAccounts.map((account) => connection.getTokenAccountBalance(account)));
+      assert.equal(balances[0].value.amount, "60000");
+      assert.equal(balances[1].value.amount, "40000");
+    })
+
+    it("should fall back to the royalties of the collection",async()=>{
+      await program.methods.createCollection({
+        name: "My Royalties Collection",
+        uri: "https://example.com/collection.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      await program.methods.addRoyaltiesPluginToCollection({
+        basisPoints: 1_000,
+        creators: [
+          { address: creators[0].publicKey, percentage: 60 },
+          { address: creators[1].publicKey, percentage: 40 },
+        ],
+        ruleSet: { none: {} },
+        delegate: null,
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc().then(log);
+
+      // The asset has no royalties plugin of its own
+      await program.methods.createNft({
+        name: "My NFT",
+        uri: "https://example.com/nft.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        asset: asset.publicKey,
+        collection: collection.publicKey,
+        authority: payer.publicKey,
+        owner: null,
+        updateAuthority: null,
+        collectionConfig: null,
+        collectionAuthority: null,
+        mintRecord: null,
+      }).signers([payer.payer, asset]).rpc().then(log);
+
+      const before = await Promise.all(creators.map((creator) => connection.getBalance(creator.publicKey)));
+
+      await program.methods.settleRoyalties({ price: new anchor.BN(anchor.web3.LAMPORTS_PER_SOL) })
+        .accountsPartial({ ...settlementAccounts(), collection: collection.publicKey })
+        .remainingAccounts(creators.map((creator) => ({ pubkey: creator.publicKey, isSigner: false, isWritable: true })))
+        .signers([payer.payer]).rpc().then(log);
+
+      const after = await Promise.all(creators.map((creator) => connection.getBalance(creator.publicKey)));
+      assert.equal(after[0] - before[0], anchor.web3.LAMPORTS_PER_SOL * 0.06);
+      assert.equal(after[1] - before[1], anchor.web3.LAMPORTS_PER_SOL * 0.04);
+    })
+  })
+
+  describe("Covers collection offers",()=>{
+    const price = new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 20);
+
+    const offerPda = (collectionKey: anchor.web3.PublicKey, buyerKey: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("offer"), collectionKey.toBuffer(), buyerKey.toBuffer()],
+        program.programId
+      )[0];
+
+    // Creates the collection and one asset in it for each keypair, owned by the payer
+    const createCollectionAssets = async (assets: anchor.web3.Keypair[]) => {
+      await program.methods.createCollection({
+        name: "My Offers Collection",
+        uri: "https://example.com/collection.json",
+      }).accountsPartial({
+        payer: payer.publicKey,
+        collection: collection.publicKey,
+        updateAuthority: payer.publicKey,
+      }).signers([payer.payer, collection]).rpc().then(log);
+
+      for (const item of assets) {
+        await program.methods.createNft({
+          name: "My NFT",
+          uri: "https://example.com/nft.json",
+        }).accountsPartial({
+          payer: payer.publicKey,
+          asset: item.publicKey,
+          collection: collection.publicKey,
+          authority: payer.publicKey,
+          owner: null,
+          updateAuthority: null,
+          collectionConfig: null,
+          collectionAuthority: null,
+          mintRecord: null,
+        }).signers([payer.payer, item]).rpc().then(log);
+      }
+    };
+
+    const makeOffer = async (quantity: number, expiry: anchor.BN | null) => {
+      const buyer = anchor.web3.Keypair.generate();
+      await provider.sendAndConfirm(new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: payer.publicKey,
+          toPubkey: buyer.publicKey,
+          lamports: anchor.web3.LAMPORTS_PER_SOL / 2,
+        })
+      ));
+
+      const offer = offerPda(collection.publicKey, buyer.publicKey);
+      await program.methods.makeOffer({ price, quantity, expiry }).accountsPartial({
+        buyer: buyer.publicKey,
+        collection: collection.publicKey,
+        offer,
+      }).signers([buyer]).rpc().then(log);
+      return { buyer, offer };
+    };
+
+    const acceptOffer = (buyer: anchor.web3.PublicKey, offer: anchor.web3.PublicKey, assetKey: anchor.web3.PublicKey) =>
+      program.methods.acceptOffer().accountsPartial({
+        seller: payer.publicKey,
+        buyer,
+        asset: assetKey,
+        collection: collection.publicKey,
+        offer,
+        logWrapper: null,
+      }).signers([payer.payer]).rpc();
+
+    it("should fill an offer asset by asset and refund the rest on cancel",async()=>{
+      const second = anchor.web3.Keypair.generate();
+      await createCollectionAssets([asset, second]);
+      const { buyer, offer } = await makeOffer(3, null);
+
+      const sellerBefore = await connection.getBalance(payer.publicKey);
+      await acceptOffer(buyer.publicKey, offer, asset.publicKey).then(log);
+      assert.isAbove(await connection.getBalance(payer.publicKey), sellerBefore);
+
+      let offerAccount = await program.account.offer.fetch(offer);
+      assert.equal(offerAccount.quantity, 2);
+
+      await acceptOffer(buyer.publicKey, offer, second.publicKey).then(log);
+      offerAccount = await program.account.offer.fetch(offer);
+      assert.equal(offerAccount.quantity, 1);
+
+      // the buyer owns both assets
+      for (const item of [asset, second]) {
+        await program.methods.transferNft().accountsPartial({
+          payer: buyer.publicKey,
+          asset: item.publicKey,
+          collection: collection.publicKey,
+          authority: buyer.publicKey,
+          newOwner: anchor.web3.Keypair.generate().publicKey,
+        }).signers([buyer]).rpc().then(log);
+      }
+
+      const buyerBefore = await connection.getBalance(buyer.publicKey);
+      await program.methods.cancelOffer().accountsPartial({
+        buyer: buyer.publicKey,
+        offer,
+      }).signers([buyer]).rpc().then(log);
+      assert.isNull(await connection.getAccountInfo(offer));
+      assert.isAbove(await connection.getBalance(buyer.publicKey), buyerBefore + price.toNumber() - 10_000);
+    })
+
+    it("should close a filled offer",async()=>{
+      await createCollectionAssets([asset]);
+      const { buyer, offer } = await makeOffer(1, null);
+
+      await acceptOffer(buyer.publicKey, offer, asset.publicKey).then(log);
+      assert.isNull(await connection.getAccountInfo(offer));
+    })
+
+    it("should only refund an offer once it has expired",async()=>{
+      await createCollectionAssets([asset]);
+      const expiry = new anchor.BN(Math.floor(Date.now() / 1000) + 3);
+      const { buyer, offer } = await makeOffer(1, expiry);
+
+      try {
+        await program.methods.refundExpiredOffer().accountsPartial({
+          buyer: buyer.publicKey,
+          offer,
+        }).rpc();
+        assert.fail("the offer has not expired yet");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "OfferNotExpired");
+      }
+
+      await new Promise((resolve) => setTimeout(resolve, 5000));
+
+      try {
+        await acceptOffer(buyer.publicKey, offer, asset.publicKey);
+        assert.fail("an expired offer can not be accepted");
+      } catch (err) {
+        assert.equal((err as anchor.AnchorError).error.errorCode.code, "OfferExpired");
+      }
 
+      // anyone can send the refund
+      await program.methods.refundExpiredOffer().accountsPartial({
+        buyer: buyer.publicKey,
+        offer,
+      }).rpc().then(log);
+      assert.isNull(await connection.getAccountInfo(offer));
     })
   })
 